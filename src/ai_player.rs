@@ -1,12 +1,17 @@
 use crate::description::LevelDescription;
-use crate::game_state::GameGridState;
 use crate::grid::Grid;
-use crate::strategy::SolvingStrategy;
-use std::time::{Duration, Instant};
+use crate::strategy::{GridSolvingStrategy, SolvingStrategy, StrategyKind};
+use crate::strategy::simple::SimpleStrategy;
+use crate::strategy::overlap::OverlapStrategy;
+use crate::strategy::line_solver::LineSolverStrategy;
+use crate::strategy::probing::ProbingStrategy;
+use crate::strategy::backtracking::BacktrackingStrategy;
+use std::time::Instant;
 use crate::line::{ColLine, RowLine};
 
 pub struct AiPlayer {
     pub engines: Vec<Box<dyn SolvingStrategy>>,
+    pub grid_engines: Vec<Box<dyn GridSolvingStrategy>>,
     current_engine: usize,
     is_active: bool,
     last_update_instant: Instant
@@ -16,16 +21,31 @@ impl AiPlayer {
     pub fn is_active(&self) -> bool {self.is_active}
     pub fn new() -> Self {
         let engines: Vec<Box<dyn SolvingStrategy>> = Vec::new();
+        let grid_engines: Vec<Box<dyn GridSolvingStrategy>> = Vec::new();
         Self {
             engines,
+            grid_engines,
             is_active: false,
             current_engine: 0,
             last_update_instant: Instant::now()
         }
     }
+    pub fn with_strategies(strategies: &[StrategyKind]) -> Self {
+        let mut ai_player = Self::new();
+        for strategy in strategies {
+            match strategy {
+                StrategyKind::Simple => ai_player.engines.push(Box::new(SimpleStrategy {})),
+                StrategyKind::Overlap => ai_player.engines.push(Box::new(OverlapStrategy {})),
+                StrategyKind::Line => ai_player.engines.push(Box::new(LineSolverStrategy {})),
+                StrategyKind::Probing => ai_player.grid_engines.push(Box::new(ProbingStrategy {})),
+                StrategyKind::Backtracking => ai_player.grid_engines.push(Box::new(BacktrackingStrategy::default()))
+            }
+        }
+        ai_player
+    }
     pub fn play_single_turn_emergency<GridType: Grid>(&self, level_description: &LevelDescription, grid: &mut GridType) {
-        for i in 0..self.engines.len() {
-            if process_lines(level_description, grid, &*self.engines[i]) {//&* is strange
+        for i in 0..self.engines_count() {
+            if self.run_engine(i, level_description, grid) {
                 return;
             }
         }
@@ -45,17 +65,33 @@ impl AiPlayer {
         self.last_update_instant = Instant::now();
     }
     fn play_single_turn_with_engines_order_memory<GridType: Grid>(&mut self, level_description: &LevelDescription, grid: &mut GridType) {
-        for i in 0..self.engines.len() {
-            self.current_engine = (self.current_engine + i) % self.engines.len();
-            if process_lines(level_description, grid, &*self.engines[self.current_engine]) {
+        for i in 0..self.engines_count() {
+            self.current_engine = (self.current_engine + i) % self.engines_count();
+            if self.run_engine(self.current_engine, level_description, grid) {
                 return;
             }
         }
     }
+    fn engines_count(&self) -> usize {
+        self.engines.len() + self.grid_engines.len()
+    }
+    ///line engines go first, grid engines are numbered after them
+    fn run_engine<GridType: Grid>(&self, engine: usize, level_description: &LevelDescription, grid: &mut GridType) -> bool {
+        if engine < self.engines.len() {
+            process_lines(level_description, grid, &*self.engines[engine])//&* is strange
+        }
+        else {
+            self.grid_engines[engine - self.engines.len()].process_grid(level_description, grid)
+        }
+    }
     pub fn pause_play(&mut self) {
         self.is_active = false
     }
 }
+impl Default for AiPlayer {
+    fn default() -> Self { Self::new() }
+}
+
 ///returns true if strategy make any decision, false if not
 fn process_lines<GridType: Grid>(level_description: &LevelDescription, grid: &mut GridType, strategy: &dyn SolvingStrategy) -> bool {
     for (row_num, row_description) in level_description.rows.iter().enumerate() {
@@ -0,0 +1,159 @@
+use std::path;
+
+use ggez::{Context, ContextBuilder, GameResult};
+use ggez::conf::WindowMode;
+use ggez::event::{self, EventHandler, EventLoop, MouseButton};
+use ggez::graphics;
+use ggez::input::keyboard::KeyInput;
+
+use nonogram_gamer_rs::description::LevelDescriptionTemplate;
+use nonogram_gamer_rs::format::load_level;
+use nonogram_gamer_rs::game_state::GameState;
+use nonogram_gamer_rs::save::{default_save_path, SaveGame};
+
+use crate::level_menu::LevelMenu;
+use crate::my_game::{MyGame, MAIN_FONT};
+
+/// Asked for by a scene, carried out by `App` before the next frame
+pub enum Transition {
+    OpenLevel(String),
+    OpenMenu
+}
+
+/// Owns the window and passes its events to the level being played, or to the menu when there is none
+pub struct App {
+    /// `None` when a single level was opened
+    menu: Option<LevelMenu>,
+    game: Option<Box<MyGame>>,
+    profile_path: String
+}
+
+fn build_context() -> GameResult<(Context, EventLoop<()>)> {
+    let resource_dir = if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+        let mut path = path::PathBuf::from(manifest_dir);
+        path.push("resources");
+        path
+    } else {
+        path::PathBuf::from("./resources")
+    };
+    let (mut ctx, event_loop) = ContextBuilder::new("Nonogram-gamer", "Alexei + Dmitri")
+        .add_resource_path(resource_dir)
+        .window_mode(WindowMode::default().resizable(true))
+        .build()?;
+    let font = graphics::FontData::from_path(&ctx, "/LiberationMono-Regular.ttf")?;
+    ctx.gfx.add_font(MAIN_FONT, font);
+    Ok((ctx, event_loop))
+}
+
+/// Plays one level, closing the window quits
+pub fn run_level(lvl_desc: LevelDescriptionTemplate, game_state: GameState, level_path: String, save_path: String, profile_path: String) -> GameResult {
+    let (mut ctx, event_loop) = build_context()?;
+    let my_game = MyGame::new(&mut ctx, lvl_desc, game_state, level_path, save_path, profile_path.clone(), false);
+    let app = App { menu: None, game: Some(Box::new(my_game)), profile_path };
+    event::run(ctx, event_loop, app);
+}
+
+/// Starts with the menu of the levels in the directory
+pub fn run_menu(levels_dir: String, profile_path: String) -> GameResult {
+    let (mut ctx, event_loop) = build_context()?;
+    let menu = LevelMenu::new(&mut ctx, &levels_dir, &profile_path);
+    let app = App { menu: Some(menu), game: None, profile_path };
+    event::run(ctx, event_loop, app);
+}
+
+/// Loads the level with the progress from its save file, a save of a changed level is dropped
+fn open_level(ctx: &mut Context, level_path: String, profile_path: String) -> Result<MyGame, Box<dyn std::error::Error>> {
+    let lvl_desc = load_level(&level_path)?;
+    let save_path = default_save_path(&level_path);
+    let game_state = SaveGame::from_file(&save_path)
+        .and_then(|x| x.restore(&lvl_desc))
+        .unwrap_or_else(|_| GameState::new(lvl_desc.clone().into()));
+    Ok(MyGame::new(ctx, lvl_desc, game_state, level_path, save_path, profile_path, true))
+}
+
+impl App {
+    fn handler(&mut self) -> &mut dyn EventHandler {
+        match (self.game.as_deref_mut(), self.menu.as_mut()) {
+            (Some(game), _) => game,
+            (None, Some(menu)) => menu,
+            (None, None) => unreachable!("a single level quits instead of going back to the menu")
+        }
+    }
+
+    fn switch_scene(&mut self, ctx: &mut Context) {
+        let transition = match (self.game.as_mut(), self.menu.as_mut()) {
+            (Some(game), _) => game.transition.take(),
+            (None, Some(menu)) => menu.transition.take(),
+            (None, None) => None
+        };
+        match transition {
+            Some(Transition::OpenLevel(level_path)) => match open_level(ctx, level_path.clone(), self.profile_path.clone()) {
+                Ok(mut game) => {
+                    // a level the menu has rated already is not rated again
+                    if let Some(difficulty) = self.menu.as_ref().and_then(|x| x.difficulty(&level_path)) {
+                        game.set_difficulty(difficulty);
+                    }
+                    self.game = Some(Box::new(game));
+                },
+                Err(e) => if let Some(menu) = self.menu.as_mut() {
+                    menu.error = Some(format!("{}: {}", level_path, e));
+                }
+            },
+            Some(Transition::OpenMenu) => {
+                if let Some(game) = self.game.as_mut() {
+                    game.save();
+                }
+                // only the progress is read again, the levels keep their ratings
+                match self.menu.as_mut() {
+                    Some(menu) => {
+                        self.game = None;
+                        LevelMenu::show(ctx);
+                        menu.refresh(&self.profile_path);
+                    },
+                    None => ctx.request_quit()
+                }
+            },
+            None => {}
+        }
+    }
+}
+
+impl EventHandler for App {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        self.handler().update(ctx)?;
+        self.switch_scene(ctx);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.handler().draw(ctx)
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeated: bool) -> GameResult {
+        self.handler().key_down_event(ctx, input, repeated)
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, input: KeyInput) -> GameResult {
+        self.handler().key_up_event(ctx, input)
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) -> GameResult {
+        self.handler().mouse_button_down_event(ctx, button, x, y)
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) -> GameResult {
+        self.handler().mouse_wheel_event(ctx, x, y)
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) -> GameResult {
+        self.handler().mouse_motion_event(ctx, x, y, dx, dy)
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        self.handler().resize_event(ctx, width, height)
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> GameResult<bool> {
+        self.handler().quit_event(ctx)
+    }
+}
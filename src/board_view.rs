@@ -0,0 +1,209 @@
+use ggez::glam::Vec2;
+use ggez::graphics::Rect;
+use ggez::mint::Point2;
+
+/// Cell size the window is opened with
+pub const CELL_SIZE: f32 = 100.0;
+const MAX_CELL_SIZE: f32 = 2.0 * CELL_SIZE;
+/// Windows are not opened larger than this, bigger boards are scrolled
+const MAX_WINDOW_SIZE: Vec2 = Vec2::new(1200.0, 900.0);
+
+/// Where the board, the clue headers and the button line are on the screen.
+/// Cells fit the window when `zoom` is 1, zooming in scrolls the board by `pan`
+/// while the clue headers stay pinned at the top and left edges.
+pub struct BoardView {
+    width: usize,
+    height: usize,
+    row_clue_cells: usize,
+    col_clue_cells: usize,
+    /// cells the button line needs, it is shrunk to fit the window's width
+    button_cells: usize,
+    /// swatches on the lines under the buttons, as many lines as they need
+    palette_cells: usize,
+    window: Vec2,
+    zoom: f32,
+    /// offset of the board from its place right after the headers, never positive
+    pan: Vec2
+}
+
+impl BoardView {
+    pub fn new(width: usize, height: usize, row_clue_cells: usize, col_clue_cells: usize, button_cells: usize, window: Vec2) -> Self {
+        Self { width, height, row_clue_cells, col_clue_cells, button_cells, palette_cells: 0, window, zoom: 1.0, pan: Vec2::ZERO }
+    }
+
+    pub fn set_palette_cells(&mut self, palette_cells: usize) {
+        self.palette_cells = palette_cells;
+        self.clamp_pan();
+    }
+
+    /// Size of the window that shows the whole board and the palette with the default cell size, up to a limit
+    pub fn preferred_window_size(width: usize, height: usize, row_clue_cells: usize, col_clue_cells: usize, palette_cells: usize) -> Vec2 {
+        let window_width = ((row_clue_cells + width) as f32 * CELL_SIZE).min(MAX_WINDOW_SIZE.x);
+        let lines = 1 + palette_cells.div_ceil(Self::cells_per_line(window_width, CELL_SIZE));
+        Vec2::new(
+            window_width,
+            (col_clue_cells + height + lines) as f32 * CELL_SIZE
+        ).min(MAX_WINDOW_SIZE)
+    }
+
+    fn cells_per_line(window_width: f32, size: f32) -> usize {
+        ((window_width / size).floor() as usize).max(1)
+    }
+
+    /// Lines under the board: the buttons, then the palette
+    fn button_lines(&self, button_size: f32) -> usize {
+        1 + self.palette_cells.div_ceil(Self::cells_per_line(self.window.x, button_size))
+    }
+
+    /// Cell size at which the whole board and the lines of buttons fit the window
+    fn fit_cell_size(&self) -> f32 {
+        let horizontal = self.window.x / (self.row_clue_cells + self.width) as f32;
+        let fit = |lines: usize| horizontal.min(self.window.y / (self.col_clue_cells + self.height + lines) as f32);
+        // smaller cells make smaller buttons, so the palette never needs more lines than counted here
+        fit(self.button_lines(self.button_size_at(fit(1))))
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.fit_cell_size() * self.zoom
+    }
+
+    /// Buttons are not larger than the board's cells, and all of them fit the window's width
+    pub fn button_size(&self) -> f32 {
+        self.button_size_at(self.fit_cell_size())
+    }
+
+    fn button_size_at(&self, fit_cell_size: f32) -> f32 {
+        fit_cell_size.min(CELL_SIZE).min(self.window.x / self.button_cells.max(1) as f32)
+    }
+
+    /// Height of the lines of buttons at the bottom of the window
+    fn buttons_height(&self) -> f32 {
+        let size = self.button_size();
+        self.button_lines(size) as f32 * size
+    }
+
+    pub fn window(&self) -> Vec2 {
+        self.window
+    }
+
+    pub fn resize(&mut self, window: Vec2) {
+        self.window = window;
+        self.clamp_pan();
+    }
+
+    /// Changes the zoom keeping the board point under `point` in place
+    pub fn zoom_at(&mut self, point: Point2<f32>, factor: f32) {
+        let point = Vec2::new(point.x, point.y);
+        let on_board = (point - self.origin()) / self.cell_size();
+        let max_zoom = (MAX_CELL_SIZE / self.fit_cell_size()).max(1.0);
+        self.zoom = (self.zoom * factor).clamp(1.0, max_zoom);
+        self.pan = point - on_board * self.cell_size() - self.headers();
+        self.clamp_pan();
+    }
+
+    pub fn pan_by(&mut self, delta: Vec2) {
+        self.pan += delta;
+        self.clamp_pan();
+    }
+
+    /// Scrolls just enough for the cell to be in the visible part of the board
+    pub fn scroll_to(&mut self, col: usize, row: usize) {
+        let cell = self.board_cell(col, row);
+        let viewport = self.viewport();
+        if cell.x < viewport.x {
+            self.pan.x += viewport.x - cell.x;
+        }
+        else if cell.right() > viewport.right() {
+            self.pan.x -= cell.right() - viewport.right();
+        }
+        if cell.y < viewport.y {
+            self.pan.y += viewport.y - cell.y;
+        }
+        else if cell.bottom() > viewport.bottom() {
+            self.pan.y -= cell.bottom() - viewport.bottom();
+        }
+        self.clamp_pan();
+    }
+
+    fn clamp_pan(&mut self) {
+        let viewport = self.viewport();
+        let board = Vec2::new(self.width as f32, self.height as f32) * self.cell_size();
+        self.pan.x = self.pan.x.clamp((viewport.w - board.x).min(0.0), 0.0);
+        self.pan.y = self.pan.y.clamp((viewport.h - board.y).min(0.0), 0.0);
+    }
+
+    /// Width of the row clues and height of the column clues
+    pub fn headers(&self) -> Vec2 {
+        Vec2::new(self.row_clue_cells as f32, self.col_clue_cells as f32) * self.cell_size()
+    }
+
+    /// Top left corner of the board, it may be hidden under the headers
+    pub fn origin(&self) -> Vec2 {
+        self.headers() + self.pan
+    }
+
+    /// Part of the window where the board is visible
+    pub fn viewport(&self) -> Rect {
+        let headers = self.headers();
+        Rect::new(
+            headers.x,
+            headers.y,
+            (self.window.x - headers.x).max(0.0),
+            (self.window.y - self.buttons_height() - headers.y).max(0.0)
+        )
+    }
+
+    pub fn corner(&self) -> Rect {
+        let headers = self.headers();
+        Rect::new(0.0, 0.0, headers.x, headers.y)
+    }
+
+    pub fn board(&self) -> Rect {
+        let origin = self.origin();
+        Rect::new(origin.x, origin.y, self.width as f32 * self.cell_size(), self.height as f32 * self.cell_size())
+    }
+
+    pub fn board_cell(&self, col: usize, row: usize) -> Rect {
+        let origin = self.origin();
+        let size = self.cell_size();
+        Rect::new(origin.x + col as f32 * size, origin.y + row as f32 * size, size, size)
+    }
+
+    /// Place of a row clue, `slot` counts cells from the left edge of the window
+    pub fn row_clue_cell(&self, slot: usize, row: usize) -> Rect {
+        let size = self.cell_size();
+        Rect::new(slot as f32 * size, self.origin().y + row as f32 * size, size, size)
+    }
+
+    /// Place of a column clue, `slot` counts cells from the top edge of the window
+    pub fn col_clue_cell(&self, col: usize, slot: usize) -> Rect {
+        let size = self.cell_size();
+        Rect::new(self.origin().x + col as f32 * size, slot as f32 * size, size, size)
+    }
+
+    pub fn button_cell(&self, index: usize) -> Rect {
+        let size = self.button_size();
+        Rect::new(index as f32 * size, self.window.y - self.buttons_height(), size, size)
+    }
+
+    /// Place of a swatch, the palette wraps to the next line at the right edge of the window
+    pub fn palette_cell(&self, index: usize) -> Rect {
+        let size = self.button_size();
+        let per_line = Self::cells_per_line(self.window.x, size);
+        let line = 1 + index / per_line;
+        Rect::new((index % per_line) as f32 * size, self.window.y - self.buttons_height() + line as f32 * size, size, size)
+    }
+
+    /// Cell under a point of the window, only where the board is visible
+    pub fn cell_at(&self, point: Point2<f32>) -> Option<(usize, usize)> {
+        if !self.viewport().contains(point) {
+            return None;
+        }
+        let on_board = (Vec2::new(point.x, point.y) - self.origin()) / self.cell_size();
+        let (col, row) = (on_board.x.floor(), on_board.y.floor());
+        if col < 0.0 || row < 0.0 || col as usize >= self.width || row as usize >= self.height {
+            return None;
+        }
+        Some((col as usize, row as usize))
+    }
+}
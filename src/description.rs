@@ -1,45 +1,292 @@
-use ggez::GameError;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use crate::game_state::{CellState, ColorId};
+use crate::grid::Grid;
+use crate::solver::{Solutions, Solver};
+use crate::validation::{validate, yaml_item_lines, LevelError};
 
 
-#[derive(Deserialize,Debug)]
+#[derive(Serialize,Deserialize,Debug,Default,Clone)]
 pub struct LevelDescriptionTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// colours of the level, the first one is used for every clue without a colour
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub palette: Vec<PaletteColor>,
     pub rows: Vec<Vec<usize>>,
-    pub cols: Vec<Vec<usize>>
+    pub cols: Vec<Vec<usize>>,
+    /// palette index of every clue of `rows`, empty for a black and white level
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub row_colors: Vec<Vec<ColorId>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub col_colors: Vec<Vec<ColorId>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Solution>
+}
+
+/// Count and palette index of one block
+pub type Clue = (usize, ColorId);
+
+/// Clues of a black and white line
+pub fn monochrome(counts: &[usize]) -> Vec<Clue> {
+    counts.iter().map(|x| (*x, 0)).collect()
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+pub struct PaletteColor {
+    pub name: String,
+    /// hex colour as in `ff8000` or `f80`, with or without `#`
+    pub rgb: String
+}
+
+impl PaletteColor {
+    pub fn new(name: &str, rgb: &str) -> Self {
+        Self { name: name.to_string(), rgb: rgb.to_string() }
+    }
+
+    pub fn black() -> Self {
+        Self::new("black", "000000")
+    }
+
+    pub fn to_rgb(&self) -> Option<[u8; 3]> {
+        let hex = self.rgb.trim().trim_start_matches('#');
+        let digits: Vec<u8> = hex.chars().map(|x| x.to_digit(16).map(|x| x as u8)).collect::<Option<_>>()?;
+        match digits[..] {
+            [r, g, b] => Some([r * 17, g * 17, b * 17]),
+            [r1, r2, g1, g2, b1, b2] => Some([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2]),
+            _ => None
+        }
+    }
+}
+
+/// Puts together the count and colour of every clue, a missing colour is the first one
+fn with_colors(lines: &[Vec<usize>], colors: &[Vec<ColorId>]) -> Vec<Vec<Clue>> {
+    lines.iter().enumerate()
+        .map(|(i, line)| line.iter().enumerate()
+            .map(|(j, count)| (*count, colors.get(i).and_then(|x| x.get(j)).copied().unwrap_or(0)))
+            .collect())
+        .collect()
+}
+
+/// Intended picture of a level, either one string per row with `#` for filled and `.` for empty cells,
+/// or one list of `1` and `0` per row
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+#[serde(untagged)]
+pub enum Solution {
+    Picture(Vec<String>),
+    Bitmap(Vec<Vec<u8>>)
+}
+
+impl Solution {
+    pub fn from_cells(cells: &[Vec<bool>]) -> Self {
+        Solution::Picture(cells.iter()
+            .map(|row| row.iter().map(|x| if *x { '#' } else { '.' }).collect())
+            .collect())
+    }
+
+    /// Rows of filled (`true`) and empty cells, or the row and column of the first unknown symbol
+    pub fn to_cells(&self) -> Result<Vec<Vec<bool>>, (usize, usize)> {
+        match self {
+            Solution::Picture(rows) => rows.iter().enumerate()
+                .map(|(row, text)| text.chars().enumerate()
+                    .map(|(col, symbol)| match symbol {
+                        '#' | 'X' | 'x' | '1' => Ok(true),
+                        '.' | '-' | '0' | ' ' => Ok(false),
+                        _ => Err((row, col))
+                    })
+                    .collect())
+                .collect(),
+            Solution::Bitmap(rows) => rows.iter().enumerate()
+                .map(|(row, cells)| cells.iter().enumerate()
+                    .map(|(col, cell)| match cell {
+                        1 => Ok(true),
+                        0 => Ok(false),
+                        _ => Err((row, col))
+                    })
+                    .collect())
+                .collect()
+        }
+    }
 }
 
 impl LevelDescriptionTemplate {
-    pub fn from_file(filepath: &str) -> Result<LevelDescriptionTemplate, GameError> {
-        let f = std::fs::File::open(filepath)?;
-        let level_description: LevelDescriptionTemplate = serde_yaml::from_reader(&f).expect("Malformed level file");
+    pub fn from_file(filepath: &str) -> Result<LevelDescriptionTemplate, LevelError> {
+        let text = std::fs::read_to_string(filepath)?;
+        let level_description: LevelDescriptionTemplate = serde_yaml::from_str(&text)?;
+        validate(&level_description, &yaml_item_lines(&text, "rows"), &yaml_item_lines(&text, "cols"))?;
         Ok(level_description)
     }
+
+    pub fn to_file(&self, filepath: &str) -> std::io::Result<()> {
+        std::fs::write(filepath, self.to_yaml())
+    }
+
+    /// Same layout as the hand written levels, one flow list of clues per line
+    pub fn to_yaml(&self) -> String {
+        let mut result = String::new();
+        let metadata = [
+            ("title", &self.title),
+            ("author", &self.author),
+            ("copyright", &self.copyright),
+            ("license", &self.license),
+            ("description", &self.description)
+        ];
+        for (key, value) in metadata {
+            if let Some(value) = value {
+                let value = serde_yaml::to_string(value).unwrap_or_default();
+                result.push_str(&format!("{}: {}\n", key, value.trim_end()));
+            }
+        }
+        if !self.tags.is_empty() {
+            result.push_str("tags:\n");
+            for tag in self.tags.iter() {
+                let tag = serde_yaml::to_string(tag).unwrap_or_default();
+                result.push_str(&format!("  - {}\n", tag.trim_end()));
+            }
+        }
+        if !self.palette.is_empty() {
+            result.push_str("palette:\n");
+            for color in self.palette.iter() {
+                let name = serde_yaml::to_string(&color.name).unwrap_or_default();
+                let rgb = serde_yaml::to_string(&color.rgb).unwrap_or_default();
+                result.push_str(&format!("  - {{name: {}, rgb: {}}}\n", name.trim_end(), rgb.trim_end()));
+            }
+        }
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        let row_colors: Vec<Vec<usize>> = self.row_colors.iter().map(|x| x.iter().map(|x| *x as usize).collect()).collect();
+        let col_colors: Vec<Vec<usize>> = self.col_colors.iter().map(|x| x.iter().map(|x| *x as usize).collect()).collect();
+        let sections = [("rows", &self.rows), ("row_colors", &row_colors), ("cols", &self.cols), ("col_colors", &col_colors)];
+        for (key, lines) in sections {
+            if lines.is_empty() && key.ends_with("_colors") {
+                continue;
+            }
+            if key == "cols" {
+                result.push('\n');
+            }
+            result.push_str(&format!("{}:\n", key));
+            for line in lines {
+                let clues: Vec<String> = line.iter().map(|x| x.to_string()).collect();
+                result.push_str(&format!("  - [{}]\n", clues.join(", ")));
+            }
+        }
+        match &self.solution {
+            Some(Solution::Picture(rows)) => {
+                result.push_str("\nsolution:\n");
+                for row in rows {
+                    result.push_str(&format!("  - '{}'\n", row.replace('\'', "''")));
+                }
+            },
+            Some(Solution::Bitmap(rows)) => {
+                result.push_str("\nsolution:\n");
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|x| x.to_string()).collect();
+                    result.push_str(&format!("  - [{}]\n", cells.join(", ")));
+                }
+            },
+            None => {}
+        }
+        result
+    }
+
+    /// The stored solution, or the one found by the solver when there is exactly one,
+    /// as the colour of every filled cell
+    pub fn intended_solution(&self) -> Option<Vec<Vec<Option<ColorId>>>> {
+        if let Some(solution) = &self.solution {
+            return solution.to_cells().ok()
+                .map(|cells| cells.into_iter().map(|row| row.into_iter().map(|x| x.then_some(0)).collect()).collect());
+        }
+        match Solver::new(2).solve(&self.clone().into()) {
+            Solutions::Unique(grid) => Some((0..grid.height())
+                .map(|row| grid.row_to_line(row).into_iter().map(|x| x.color()).collect())
+                .collect()),
+            _ => None
+        }
+    }
+
+    /// More than one colour, black and white levels have an empty palette or just one colour
+    pub fn is_colored(&self) -> bool {
+        self.palette.len() > 1
+    }
+
+    /// The palette, or black for a level without one
+    pub fn colors(&self) -> Vec<PaletteColor> {
+        if self.palette.is_empty() { vec![PaletteColor::black()] } else { self.palette.clone() }
+    }
+
+    pub fn row_clues(&self) -> Vec<Vec<Clue>> {
+        with_colors(&self.rows, &self.row_colors)
+    }
+
+    pub fn col_clues(&self) -> Vec<Vec<Clue>> {
+        with_colors(&self.cols, &self.col_colors)
+    }
+
+    /// `fingerprint` in hex, how save and profile files refer to the level
+    pub fn level_id(&self) -> String {
+        format!("{:016x}", self.fingerprint())
+    }
+
+    /// Stable FNV-1a hash of the clues, the same puzzle gets the same id in every file format.
+    /// Colours count only in coloured levels, so black and white levels keep their ids.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut feed = |value: u64| {
+            for byte in value.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
+        for lines in [&self.rows, &self.cols] {
+            feed(lines.len() as u64);
+            for line in lines {
+                feed(line.len() as u64);
+                line.iter().for_each(|x| feed(*x as u64));
+            }
+        }
+        if self.is_colored() {
+            for lines in [self.row_clues(), self.col_clues()] {
+                lines.iter().flatten().for_each(|(_, color)| feed(*color as u64));
+            }
+        }
+        hash
+    }
+
+    pub fn validate(&self) -> Result<(), LevelError> {
+        validate(self, &[], &[])
+    }
 }
 
 impl Into<LevelDescription> for LevelDescriptionTemplate {
     fn into(self) -> LevelDescription {
-        let Self { rows, cols } = self;
+        let to_lines = |lines: Vec<Vec<Clue>>| lines.into_iter()
+            .map(|i| LineDescription {
+                parts: i.into_iter()
+                    .map(|(count, color)| LineDescriptionPart { elements_count: count, color, is_completed: false })
+                    .collect()
+            })
+            .collect();
         LevelDescription {
-            rows: rows.into_iter()
-                .map(|i| LineDescription {
-                    parts: i.into_iter()
-                        .map(|j| LineDescriptionPart { elements_count: j, is_completed: false })
-                        .collect()
-                })
-                .collect(),
-            cols: cols.into_iter()
-                .map(|i| LineDescription {
-                    parts: i.into_iter()
-                        .map(|j| LineDescriptionPart { elements_count: j, is_completed: false })
-                        .collect()
-                })
-                .collect()
+            rows: to_lines(self.row_clues()),
+            cols: to_lines(self.col_clues()),
+            colors: self.palette.len().max(1)
         }
     }
 }
 
 pub struct LineDescriptionPart {
     pub elements_count: usize,
+    pub color: ColorId,
     pub is_completed: bool
 }
 pub struct LineDescription {
@@ -47,18 +294,42 @@ pub struct LineDescription {
 }
 pub struct LevelDescription {
     pub rows: Vec<LineDescription>,
-    pub cols: Vec<LineDescription>
+    pub cols: Vec<LineDescription>,
+    /// size of the palette, 1 for a black and white level
+    pub colors: usize
+}
+
+impl LineDescription {
+    /// Blocks with a length, the ones a line solver has to place
+    pub fn clues(&self) -> Vec<Clue> {
+        self.parts.iter().filter(|x| x.elements_count > 0).map(|x| (x.elements_count, x.color)).collect()
+    }
 }
 
 impl LevelDescription {
     pub fn row_to_line_description(&self, row_id: usize) -> Vec<usize> {
-        self.rows[row_id].parts.iter().map(|(x)| x.elements_count).collect()
+        self.rows[row_id].parts.iter().map(|x| x.elements_count).collect()
     }
     pub fn col_to_line_description(&self, col_id: usize) -> Vec<usize> {
-        self.cols[col_id].parts.iter().map(|(x)| x.elements_count).collect()
+        self.cols[col_id].parts.iter().map(|x| x.elements_count).collect()
+    }
+    pub fn row_clues(&self, row_id: usize) -> Vec<Clue> {
+        self.rows[row_id].parts.iter().map(|x| (x.elements_count, x.color)).collect()
+    }
+    pub fn col_clues(&self, col_id: usize) -> Vec<Clue> {
+        self.cols[col_id].parts.iter().map(|x| (x.elements_count, x.color)).collect()
     }
 
-    pub fn is_done(&self) -> bool {
-        self.rows.iter().all(|x| x.parts.iter().all(|y| y.is_completed )) && self.cols.iter().all(|x| x.parts.iter().all(|y| y.is_completed))
+    /// States a solver has to try for an undecided cell: the colours found in the clues
+    /// of both its row and its column, then crossed
+    pub fn cell_candidates(&self, col: usize, row: usize) -> Vec<CellState> {
+        let in_row: Vec<ColorId> = self.rows[row].parts.iter().map(|x| x.color).collect();
+        let mut result: Vec<CellState> = (0..self.colors)
+            .map(|x| x as ColorId)
+            .filter(|x| in_row.contains(x) && self.cols[col].parts.iter().any(|y| y.color == *x))
+            .map(CellState::filled)
+            .collect();
+        result.push(CellState::Crossed);
+        result
     }
 }
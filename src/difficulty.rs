@@ -0,0 +1,181 @@
+use std::fmt;
+use std::sync::mpsc;
+use crate::ai_player::AiPlayer;
+use crate::description::{LevelDescription, LevelDescriptionTemplate};
+use crate::game_state::{is_solved, GameState};
+use crate::strategy::StrategyKind;
+use crate::transaction::TransactionBuilder;
+
+/// One move of the analyzer: the weakest strategy that could make progress and how many cells it decided
+#[derive(Clone, Copy, Debug)]
+pub struct TraceStep {
+    pub strategy: StrategyKind,
+    pub cells_changed: usize
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Difficulty {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Unsolvable
+}
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Trivial => "trivial",
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+            Difficulty::Unsolvable => "unsolvable"
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+pub struct DifficultyReport {
+    pub trace: Vec<TraceStep>,
+    pub solved: bool
+}
+
+impl DifficultyReport {
+    pub fn steps_of(&self, strategy: StrategyKind) -> usize {
+        self.trace.iter().filter(|x| x.strategy == strategy).count()
+    }
+
+    /// Probing tries a value and looks for a contradiction, which players call guessing
+    pub fn needs_guessing(&self) -> bool {
+        self.steps_of(StrategyKind::Probing) > 0
+    }
+
+    pub fn needs_backtracking(&self) -> bool {
+        self.steps_of(StrategyKind::Backtracking) > 0
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        if !self.solved {
+            return Difficulty::Unsolvable;
+        }
+        match self.trace.iter().map(|x| x.strategy).max_by_key(|x| strength(*x)) {
+            None | Some(StrategyKind::Simple) => Difficulty::Trivial,
+            Some(StrategyKind::Overlap) => Difficulty::Easy,
+            Some(StrategyKind::Line) => Difficulty::Medium,
+            Some(StrategyKind::Probing) => Difficulty::Hard,
+            Some(StrategyKind::Backtracking) => Difficulty::Expert
+        }
+    }
+
+    /// Grows with the number of steps, stronger strategies weigh more.
+    /// Levels of the same difficulty can be ordered by it.
+    pub fn score(&self) -> usize {
+        self.trace.iter().map(|x| weight(x.strategy)).sum()
+    }
+}
+
+fn strength(strategy: StrategyKind) -> usize {
+    StrategyKind::ALL.iter().position(|x| *x == strategy).unwrap_or(0)
+}
+
+fn weight(strategy: StrategyKind) -> usize {
+    match strategy {
+        StrategyKind::Simple => 1,
+        StrategyKind::Overlap => 2,
+        StrategyKind::Line => 4,
+        StrategyKind::Probing => 16,
+        StrategyKind::Backtracking => 64
+    }
+}
+
+/// Plays the level from an empty grid, every move is made by the weakest strategy that can make one
+pub fn analyze(lvl_desc: LevelDescription) -> DifficultyReport {
+    let players: Vec<(StrategyKind, AiPlayer)> = StrategyKind::ALL.iter()
+        .map(|x| (*x, AiPlayer::with_strategies(&[*x])))
+        .collect();
+    let mut game_state = GameState::new(lvl_desc);
+    let mut trace = Vec::new();
+
+    'moves: loop {
+        for (strategy, ai_player) in players.iter() {
+            let mut builder = TransactionBuilder::new(game_state.grid());
+            ai_player.play_single_turn_emergency(game_state.lvl_desc(), &mut builder);
+            let transaction = builder.to_transaction(game_state.grid());
+            if !transaction.changes.is_empty() {
+                trace.push(TraceStep { strategy: *strategy, cells_changed: transaction.changes.len() });
+                game_state.apply_transaction(&transaction);
+                continue 'moves;
+            }
+        }
+        break;
+    }
+
+    let solved = is_solved(game_state.grid(), game_state.lvl_desc());
+    DifficultyReport { trace, solved }
+}
+
+/// Rates levels on a thread of its own, so that a window stays responsive while hard levels are analyzed
+pub struct BackgroundRating {
+    receiver: mpsc::Receiver<(String, Difficulty)>
+}
+
+impl BackgroundRating {
+    /// Rates the levels one after another, each difficulty comes back with the key it was given with
+    pub fn start(levels: Vec<(String, LevelDescriptionTemplate)>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (key, level) in levels {
+                // nobody is waiting for the rest once the receiver is dropped
+                if sender.send((key, analyze(level.into()).difficulty())).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { receiver }
+    }
+
+    /// Ratings finished since the last call
+    pub fn finished(&self) -> Vec<(String, Difficulty)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::description::LevelDescriptionTemplate;
+
+    #[test]
+    fn test_heart_needs_only_simple_lines() {
+        let heart = LevelDescriptionTemplate {
+            rows: vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]],
+            cols: vec![vec![2], vec![4], vec![4], vec![4], vec![2]],
+            ..Default::default()
+        };
+        let report = analyze(heart.into());
+        assert!(report.solved);
+        assert_eq!(report.steps_of(StrategyKind::Simple), 5);
+        assert_eq!(report.steps_of(StrategyKind::Overlap), 0);
+        assert_eq!(report.steps_of(StrategyKind::Line), 0);
+        assert_eq!(report.steps_of(StrategyKind::Probing), 0);
+        assert_eq!(report.steps_of(StrategyKind::Backtracking), 0);
+        assert_eq!(report.difficulty(), Difficulty::Trivial);
+    }
+
+    #[test]
+    fn test_ambiguous_level_is_unsolvable() {
+        let ambiguous = LevelDescriptionTemplate {
+            rows: vec![vec![1], vec![1]],
+            cols: vec![vec![1], vec![1]],
+            ..Default::default()
+        };
+        assert_eq!(analyze(ambiguous.into()).difficulty(), Difficulty::Unsolvable);
+    }
+}
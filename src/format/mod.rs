@@ -0,0 +1,29 @@
+use std::io;
+use std::path::Path;
+use crate::description::LevelDescriptionTemplate;
+use crate::validation::LevelError;
+
+pub mod non;
+pub mod webpbn;
+
+fn extension(filepath: &str) -> Option<&str> {
+    Path::new(filepath).extension().and_then(|x| x.to_str())
+}
+
+/// Picks the loader from the file extension, anything unknown is read as yaml
+pub fn load_level(filepath: &str) -> Result<LevelDescriptionTemplate, LevelError> {
+    match extension(filepath) {
+        Some("non") => non::from_file(filepath),
+        Some("xml") | Some("pbn") => webpbn::from_file(filepath),
+        _ => LevelDescriptionTemplate::from_file(filepath)
+    }
+}
+
+/// Picks the writer from the file extension, only yaml and webpbn xml can be written
+pub fn save_level(level: &LevelDescriptionTemplate, filepath: &str) -> io::Result<()> {
+    match extension(filepath) {
+        Some("xml") | Some("pbn") => webpbn::to_file(level, filepath),
+        Some("yaml") | Some("yml") => level.to_file(filepath),
+        _ => Err(io::Error::new(io::ErrorKind::Unsupported, format!("can not write levels to '{}', use .yaml or .xml", filepath)))
+    }
+}
@@ -0,0 +1,140 @@
+use crate::description::{LevelDescriptionTemplate, Solution};
+use crate::validation::{validate, LevelError};
+
+/// Reads a puzzle in the `.non` text format:
+/// `width`/`height` keywords, then `rows` and `columns` sections with one comma separated clue list per line.
+/// `goal` holds the solution as `0` and `1` row by row.
+/// `title`, `by`, `copyright`, `license`, `description` and the comma separated `tags` are quoted strings,
+/// unknown keywords such as `catalogue` are skipped.
+pub fn from_file(filepath: &str) -> Result<LevelDescriptionTemplate, LevelError> {
+    let text = std::fs::read_to_string(filepath)?;
+    from_str(&text)
+}
+
+pub fn from_str(text: &str) -> Result<LevelDescriptionTemplate, LevelError> {
+    let mut width = None;
+    let mut height = None;
+    let mut rows = None;
+    let mut cols = None;
+    let mut goal = None;
+    let mut level = LevelDescriptionTemplate::default();
+
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())).peekable();
+    while let Some((line_num, line)) = lines.next() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match keyword {
+            "width" => width = Some(parse_number(argument.trim(), line_num)?),
+            "height" => height = Some(parse_number(argument.trim(), line_num)?),
+            "goal" => goal = Some((quoted(argument), line_num)),
+            "title" => level.title = Some(quoted(argument)),
+            "by" => level.author = Some(quoted(argument)),
+            "copyright" => level.copyright = Some(quoted(argument)),
+            "license" => level.license = Some(quoted(argument)),
+            "description" => level.description = Some(quoted(argument)),
+            "tags" => level.tags = quoted(argument).split(',').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect(),
+            "rows" | "columns" => {
+                let count = if keyword == "rows" { height } else { width };
+                let mut clues = Vec::new();
+                let mut source_lines = Vec::new();
+                while let Some((line_num, line)) = lines.peek().copied() {
+                    let section_done = match count {
+                        Some(count) => clues.len() == count,
+                        None => line.is_empty() || line.starts_with(|c: char| c.is_ascii_alphabetic())
+                    };
+                    if section_done {
+                        break;
+                    }
+                    lines.next();
+                    clues.push(parse_clues(line, line_num)?);
+                    source_lines.push(line_num);
+                }
+                if keyword == "rows" {
+                    rows = Some((clues, source_lines));
+                }
+                else {
+                    cols = Some((clues, source_lines));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let (rows, row_lines) = rows.ok_or_else(|| parse_error(None, "no rows section"))?;
+    let (cols, col_lines) = cols.ok_or_else(|| parse_error(None, "no columns section"))?;
+    if height.is_some_and(|x| x != rows.len()) || width.is_some_and(|x| x != cols.len()) {
+        return Err(parse_error(None, "size does not match the number of clue lines"));
+    }
+
+    level.solution = match goal {
+        Some((goal, line_num)) => {
+            if goal.chars().count() != rows.len() * cols.len() {
+                return Err(parse_error(Some(line_num), "goal does not have a symbol for every cell"));
+            }
+            let symbols: Vec<char> = goal.chars().collect();
+            Some(Solution::Picture(symbols.chunks(cols.len().max(1)).map(|x| x.iter().collect()).collect()))
+        },
+        None => None
+    };
+
+    level.rows = rows;
+    level.cols = cols;
+    validate(&level, &row_lines, &col_lines)?;
+    Ok(level)
+}
+
+fn quoted(argument: &str) -> String {
+    let argument = argument.trim();
+    argument.strip_prefix('"').and_then(|x| x.strip_suffix('"')).unwrap_or(argument).to_string()
+}
+
+fn parse_clues(line: &str, line_num: usize) -> Result<Vec<usize>, LevelError> {
+    let mut clues = Vec::new();
+    for part in line.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        // a single 0 stands for an empty line
+        match parse_number(part, line_num)? {
+            0 => {},
+            clue => clues.push(clue)
+        }
+    }
+    Ok(clues)
+}
+
+fn parse_number(text: &str, line_num: usize) -> Result<usize, LevelError> {
+    text.parse().map_err(|_| parse_error(Some(line_num), &format!("'{}' is not a number", text)))
+}
+
+fn parse_error(line: Option<usize>, message: &str) -> LevelError {
+    LevelError::Parse { line, message: message.to_string() }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_non() {
+        let text = "catalogue \"heart\"\ntitle \"Heart\"\nby \"Alexei & Dmitri\"\nlicense \"CC0-1.0\"\ntags \"shapes, tiny\"\nwidth 5\nheight 5\n\nrows\n1,1\n5\n5\n3\n1\n\ncolumns\n2\n4\n4\n4\n2\n\ngoal \"0101011111111110111000100\"\n";
+        let level = from_str(text).unwrap();
+        assert_eq!(level.rows, vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]]);
+        assert_eq!(level.cols, vec![vec![2], vec![4], vec![4], vec![4], vec![2]]);
+        assert_eq!(level.title.as_deref(), Some("Heart"));
+        assert_eq!(level.author.as_deref(), Some("Alexei & Dmitri"));
+        assert_eq!(level.license.as_deref(), Some("CC0-1.0"));
+        assert_eq!(level.tags, vec!["shapes", "tiny"]);
+        assert_eq!(level.solution, Some(Solution::Picture(vec![
+            "01010".to_string(), "11111".to_string(), "11111".to_string(), "01110".to_string(), "00100".to_string()
+        ])));
+    }
+
+    #[test]
+    fn test_parse_error_has_line_number() {
+        let text = "width 2\nheight 2\nrows\n1\n0\ncolumns\n1\nx\n";
+        match from_str(text) {
+            Err(LevelError::Parse { line, .. }) => assert_eq!(line, Some(8)),
+            _ => panic!("'x' is not a clue")
+        }
+    }
+}
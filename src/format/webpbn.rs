@@ -0,0 +1,335 @@
+use std::io;
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::{EmitterConfig, XmlEvent as WriterEvent};
+use crate::description::{LevelDescriptionTemplate, PaletteColor, Solution};
+use crate::game_state::{color_symbol, ColorId};
+use crate::validation::LevelError;
+
+/// A count with the name of its colour, `None` for the default colour
+type Count = (usize, Option<String>);
+
+/// Reads the first puzzle of a webpbn xml file:
+/// `puzzle` with `clues type="rows"` and `clues type="columns"`, each made of `line` and `count` elements.
+/// The `image` of a `solution type="goal"` is kept as the solution, one `|X.X|` row per line.
+/// `license` and `tag` are not part of the webpbn format, they are read and written to keep the level's metadata.
+/// Counts of another colour than `defaultcolor` make a coloured level, its palette starts with the default colour
+/// and holds every `color` but the background. The goal of a coloured puzzle is not kept.
+pub fn from_file(filepath: &str) -> Result<LevelDescriptionTemplate, LevelError> {
+    let text = std::fs::read_to_string(filepath)?;
+    from_str(&text)
+}
+
+pub fn from_str(text: &str) -> Result<LevelDescriptionTemplate, LevelError> {
+    let mut level = LevelDescriptionTemplate::default();
+    let mut rows = None;
+    let mut cols = None;
+
+    // the clue list being read and whether it is rows
+    let mut clues: Option<(bool, Vec<Vec<Count>>)> = None;
+    let mut default_color = "black".to_string();
+    let mut background_color = "white".to_string();
+    // declared colours by name and the colour of the count or `color` element being read
+    let mut colors: Vec<PaletteColor> = Vec::new();
+    let mut color_name = None;
+    let mut path: Vec<String> = Vec::new();
+    let mut characters = String::new();
+    let mut puzzles_seen = 0;
+    let mut in_goal = false;
+
+    let mut reader = EventReader::from_str(text);
+    loop {
+        let event = reader.next().map_err(|e| LevelError::Parse {
+            line: Some(e.position().row as usize + 1),
+            message: e.msg().to_string()
+        })?;
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                characters.clear();
+                match name.local_name.as_str() {
+                    "puzzle" => {
+                        puzzles_seen += 1;
+                        if puzzles_seen == 1 {
+                            for attribute in attributes.iter() {
+                                match attribute.name.local_name.as_str() {
+                                    "defaultcolor" => default_color = attribute.value.clone(),
+                                    "backgroundcolor" => background_color = attribute.value.clone(),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    },
+                    "color" => color_name = attributes.iter().find(|x| x.name.local_name == "name").map(|x| x.value.clone()),
+                    "count" => color_name = attributes.iter().find(|x| x.name.local_name == "color").map(|x| x.value.clone()),
+                    "clues" if puzzles_seen == 1 => {
+                        let is_rows = attributes.iter()
+                            .find(|x| x.name.local_name == "type")
+                            .map(|x| x.value == "rows")
+                            .unwrap_or(false);
+                        clues = Some((is_rows, Vec::new()));
+                    },
+                    "solution" if puzzles_seen == 1 => {
+                        in_goal = attributes.iter()
+                            .find(|x| x.name.local_name == "type")
+                            .map(|x| x.value == "goal")
+                            .unwrap_or(true);
+                    },
+                    "line" => if let Some((_, lines)) = clues.as_mut() {
+                        lines.push(Vec::new());
+                    },
+                    _ => {}
+                }
+                path.push(name.local_name);
+            },
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => characters.push_str(&text),
+            XmlEvent::EndElement { .. } => {
+                let name = path.pop().unwrap_or_default();
+                let in_first_puzzle = puzzles_seen == 1 && path.last().map(|x| x.as_str()) == Some("puzzle");
+                let value = characters.trim().to_string();
+                match name.as_str() {
+                    "count" => if let Some(line) = clues.as_mut().and_then(|(_, lines)| lines.last_mut()) {
+                        let count = value.parse().map_err(|_| LevelError::Parse {
+                            line: Some(reader.position().row as usize + 1),
+                            message: format!("'{}' is not a number", value)
+                        })?;
+                        line.push((count, color_name.take()));
+                    },
+                    "color" if in_first_puzzle => if let Some(name) = color_name.take() {
+                        colors.push(PaletteColor::new(&name, &value));
+                    },
+                    "clues" => if let Some((is_rows, lines)) = clues.take() {
+                        if is_rows { rows = Some(lines) } else { cols = Some(lines) }
+                    },
+                    "solution" => in_goal = false,
+                    "image" if in_goal && level.solution.is_none() => {
+                        let rows = value.lines()
+                            .map(|x| x.trim().trim_matches('|').to_string())
+                            .filter(|x| !x.is_empty())
+                            .collect();
+                        level.solution = Some(Solution::Picture(rows));
+                    },
+                    "title" if in_first_puzzle => level.title = Some(value),
+                    "author" if in_first_puzzle => level.author = Some(value),
+                    "copyright" if in_first_puzzle => level.copyright = Some(value),
+                    "license" if in_first_puzzle => level.license = Some(value),
+                    "description" if in_first_puzzle => level.description = Some(value),
+                    "tag" if in_first_puzzle => level.tags.push(value),
+                    _ => {}
+                }
+                characters.clear();
+            },
+            XmlEvent::EndDocument => break,
+            _ => {}
+        }
+    }
+
+    let rows = rows.ok_or_else(|| LevelError::Parse { line: None, message: "no row clues".to_string() })?;
+    let cols = cols.ok_or_else(|| LevelError::Parse { line: None, message: "no column clues".to_string() })?;
+    level.rows = rows.iter().map(|x| x.iter().map(|(count, _)| *count).collect()).collect();
+    level.cols = cols.iter().map(|x| x.iter().map(|(count, _)| *count).collect()).collect();
+
+    let is_colored = rows.iter().chain(cols.iter()).flatten().any(|(_, color)| color.as_ref().is_some_and(|x| *x != default_color));
+    if is_colored {
+        let default = colors.iter().find(|x| x.name == default_color).cloned().unwrap_or_else(|| PaletteColor::new(&default_color, "000"));
+        level.palette = std::iter::once(default)
+            .chain(colors.into_iter().filter(|x| x.name != default_color && x.name != background_color))
+            .collect();
+        level.row_colors = palette_indices(&level.palette, &rows)?;
+        level.col_colors = palette_indices(&level.palette, &cols)?;
+        level.solution = None;
+    }
+    level.validate()?;
+    Ok(level)
+}
+
+fn palette_indices(palette: &[PaletteColor], lines: &[Vec<Count>]) -> Result<Vec<Vec<ColorId>>, LevelError> {
+    lines.iter()
+        .map(|line| line.iter()
+            .map(|(_, color)| match color {
+                None => Ok(0),
+                Some(name) => palette.iter().position(|x| x.name == *name)
+                    .map(|x| x as ColorId)
+                    .ok_or_else(|| LevelError::Parse { line: None, message: format!("unknown colour '{}'", name) })
+            })
+            .collect())
+        .collect()
+}
+
+pub fn to_file(level: &LevelDescriptionTemplate, filepath: &str) -> io::Result<()> {
+    std::fs::write(filepath, to_string(level)?)
+}
+
+pub fn to_string(level: &LevelDescriptionTemplate) -> io::Result<String> {
+    let mut output = Vec::new();
+    let mut writer = EmitterConfig::new().perform_indent(true).create_writer(&mut output);
+    let to_io = |e: xml::writer::Error| io::Error::other(e);
+
+    let palette = level.colors();
+    let background = if palette.iter().any(|x| x.name == "white") { "background" } else { "white" };
+    writer.write(WriterEvent::start_element("puzzleset")).map_err(to_io)?;
+    let puzzle = WriterEvent::start_element("puzzle").attr("type", "grid").attr("defaultcolor", &palette[0].name);
+    writer.write(if background == "white" { puzzle } else { puzzle.attr("backgroundcolor", background) }).map_err(to_io)?;
+
+    let metadata = [
+        ("title", level.title.as_ref()),
+        ("author", level.author.as_ref()),
+        ("copyright", level.copyright.as_ref()),
+        ("license", level.license.as_ref()),
+        ("description", level.description.as_ref())
+    ];
+    let tags = level.tags.iter().map(|x| ("tag", Some(x)));
+    for (name, value) in metadata.into_iter().chain(tags) {
+        if let Some(value) = value {
+            writer.write(WriterEvent::start_element(name)).map_err(to_io)?;
+            writer.write(WriterEvent::characters(value)).map_err(to_io)?;
+            writer.write(WriterEvent::end_element()).map_err(to_io)?;
+        }
+    }
+
+    writer.write(WriterEvent::start_element("color").attr("name", background).attr("char", ".")).map_err(to_io)?;
+    writer.write(WriterEvent::characters("fff")).map_err(to_io)?;
+    writer.write(WriterEvent::end_element()).map_err(to_io)?;
+    for (index, color) in palette.iter().enumerate() {
+        // lower case letters stay clear of `X`
+        let symbol = if index == 0 { 'X' } else { color_symbol(index as ColorId).to_ascii_lowercase() };
+        writer.write(WriterEvent::start_element("color").attr("name", &color.name).attr("char", &symbol.to_string())).map_err(to_io)?;
+        writer.write(WriterEvent::characters(&color.rgb)).map_err(to_io)?;
+        writer.write(WriterEvent::end_element()).map_err(to_io)?;
+    }
+
+    for (kind, lines) in [("columns", level.col_clues()), ("rows", level.row_clues())] {
+        writer.write(WriterEvent::start_element("clues").attr("type", kind)).map_err(to_io)?;
+        for line in lines {
+            writer.write(WriterEvent::start_element("line")).map_err(to_io)?;
+            for (count, color) in line {
+                let element = WriterEvent::start_element("count");
+                let element = if color == 0 { element } else { element.attr("color", &palette[color as usize].name) };
+                writer.write(element).map_err(to_io)?;
+                writer.write(WriterEvent::characters(&count.to_string())).map_err(to_io)?;
+                writer.write(WriterEvent::end_element()).map_err(to_io)?;
+            }
+            writer.write(WriterEvent::end_element()).map_err(to_io)?;
+        }
+        writer.write(WriterEvent::end_element()).map_err(to_io)?;
+    }
+
+    if let Some(Ok(cells)) = level.solution.as_ref().filter(|_| !level.is_colored()).map(|x| x.to_cells()) {
+        let mut image = String::from("\n");
+        for row in cells {
+            let row: String = row.iter().map(|x| if *x { 'X' } else { '.' }).collect();
+            image.push_str(&format!("|{}|\n", row));
+        }
+        writer.write(WriterEvent::start_element("solution").attr("type", "goal")).map_err(to_io)?;
+        writer.write(WriterEvent::start_element("image")).map_err(to_io)?;
+        writer.write(WriterEvent::characters(&image)).map_err(to_io)?;
+        writer.write(WriterEvent::end_element()).map_err(to_io)?;
+        writer.write(WriterEvent::end_element()).map_err(to_io)?;
+    }
+
+    writer.write(WriterEvent::end_element()).map_err(to_io)?;
+    writer.write(WriterEvent::end_element()).map_err(to_io)?;
+
+    String::from_utf8(output).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEART: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">
+<puzzleset>
+<puzzle type="grid" defaultcolor="black">
+<source>test</source>
+<title>Heart</title>
+<author>Alexei &amp; Dmitri</author>
+<copyright>CC0</copyright>
+<license>CC0-1.0</license>
+<description>A small heart.</description>
+<tag>shapes</tag>
+<tag>tiny</tag>
+<color name="white" char=".">fff</color>
+<color name="black" char="X">000</color>
+<clues type="columns">
+<line><count>2</count></line>
+<line><count>4</count></line>
+<line><count>4</count></line>
+<line><count>4</count></line>
+<line><count>2</count></line>
+</clues>
+<clues type="rows">
+<line><count>1</count><count>1</count></line>
+<line><count>5</count></line>
+<line><count>5</count></line>
+<line><count>3</count></line>
+<line><count>1</count></line>
+</clues>
+<solution type="goal">
+<image>
+|.X.X.|
+|XXXXX|
+|XXXXX|
+|.XXX.|
+|..X..|
+</image>
+</solution>
+</puzzle>
+</puzzleset>
+"#;
+
+    #[test]
+    fn test_read_webpbn() {
+        let level = from_str(HEART).unwrap();
+        assert_eq!(level.title.as_deref(), Some("Heart"));
+        assert_eq!(level.author.as_deref(), Some("Alexei & Dmitri"));
+        assert_eq!(level.copyright.as_deref(), Some("CC0"));
+        assert_eq!(level.license.as_deref(), Some("CC0-1.0"));
+        assert_eq!(level.description.as_deref(), Some("A small heart."));
+        assert_eq!(level.tags, vec!["shapes", "tiny"]);
+        assert_eq!(level.rows, vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]]);
+        assert_eq!(level.cols, vec![vec![2], vec![4], vec![4], vec![4], vec![2]]);
+    }
+
+    #[test]
+    fn test_write_and_read_back() {
+        let level = from_str(HEART).unwrap();
+        let written = to_string(&level).unwrap();
+        let read_back = from_str(&written).unwrap();
+        assert_eq!(read_back.title, level.title);
+        assert_eq!(read_back.author, level.author);
+        assert_eq!(read_back.license, level.license);
+        assert_eq!(read_back.description, level.description);
+        assert_eq!(read_back.tags, level.tags);
+        assert_eq!(read_back.rows, level.rows);
+        assert_eq!(read_back.cols, level.cols);
+        assert_eq!(read_back.solution.unwrap().to_cells(), level.solution.unwrap().to_cells());
+    }
+
+    #[test]
+    fn test_colored_puzzle() {
+        let text = r#"<puzzleset>
+<puzzle type="grid" defaultcolor="black">
+<color name="white" char=".">fff</color>
+<color name="black" char="X">000</color>
+<color name="red" char="r">f00</color>
+<clues type="columns">
+<line><count color="red">1</count></line>
+<line><count>1</count></line>
+</clues>
+<clues type="rows">
+<line><count color="red">1</count><count color="black">1</count></line>
+</clues>
+</puzzle>
+</puzzleset>
+"#;
+        let level = from_str(text).unwrap();
+        assert_eq!(level.palette, vec![PaletteColor::new("black", "000"), PaletteColor::new("red", "f00")]);
+        assert_eq!(level.row_colors, vec![vec![1, 0]]);
+        assert_eq!(level.col_colors, vec![vec![1], vec![0]]);
+
+        let read_back = from_str(&to_string(&level).unwrap()).unwrap();
+        assert_eq!(read_back.palette, level.palette);
+        assert_eq!(read_back.level_id(), level.level_id());
+        assert_eq!((read_back.row_colors, read_back.col_colors), (level.row_colors, level.col_colors));
+    }
+}
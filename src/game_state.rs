@@ -1,26 +1,92 @@
-use ggez::input::gamepad::gilrs::GilrsBuilder;
-
-use crate::description::LevelDescription;
+use serde::{Deserialize, Serialize};
+use crate::description::{LevelDescription, Clue};
 use crate::grid::Grid;
+use crate::history::{NodeId, UndoTree};
+use crate::strategy::overlap::{leftmost_placement, rightmost_placement};
 use crate::transaction::{Transaction, TransactionBuilder};
 
 
-#[derive(Clone, Copy, PartialEq)]
+/// Index into the palette of a level, the first colour is the one of black and white levels
+pub type ColorId = u8;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum CellState {
     Empty,
+    /// filled with the first colour of the palette
     Filled,
-    Crossed
+    Crossed,
+    /// filled with any other colour, never `Colored(0)`
+    Colored(ColorId)
+}
+
+impl CellState {
+    pub fn filled(color: ColorId) -> Self {
+        if color == 0 { CellState::Filled } else { CellState::Colored(color) }
+    }
+
+    pub fn color(&self) -> Option<ColorId> {
+        match self {
+            CellState::Filled => Some(0),
+            CellState::Colored(color) => Some(*color),
+            _ => None
+        }
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.color().is_some()
+    }
+}
+
+/// Colours that have a symbol of their own
+pub const MAX_COLORS: usize = 36;
+
+/// Symbol of a filled cell in save files and text output: `#` for the first colour,
+/// then `1`-`9` and `A`-`Z`
+pub fn color_symbol(color: ColorId) -> char {
+    match color {
+        0 => '#',
+        1..=9 => (b'0' + color) as char,
+        _ => (b'A' + color - 10) as char
+    }
 }
 
+pub fn symbol_color(symbol: char) -> Option<ColorId> {
+    match symbol {
+        '#' => Some(0),
+        '1'..='9' => Some(symbol as u8 - b'0'),
+        'A'..='Z' => Some(symbol as u8 - b'A' + 10),
+        _ => None
+    }
+}
+
+#[derive(Clone)]
 pub struct  GameGridState {
     cells: Vec<Vec<CellState>>, // в ГРИДЕ хранятся СТРОКИ блять
     height: usize,
     width: usize,
 }
+/// Counters of one game, they are kept in its save file
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct GameStats {
+    /// time spent on the level, counted by the window
+    #[serde(default)]
+    pub seconds: f64,
+    /// undo steps made, by the player or the AI
+    #[serde(default)]
+    pub moves: usize,
+    #[serde(default)]
+    pub undos: usize,
+    #[serde(default)]
+    pub ai_helped: bool
+}
+
 pub struct GameState {
     lvl_desc: LevelDescription,
     grid: GameGridState,
-    move_queue: Vec<Transaction>
+    history: UndoTree,
+    /// changes of the action begun with `begin_action`, they become one undo step
+    pending_action: Option<Transaction>,
+    stats: GameStats
 }
 
 impl Grid for GameGridState {
@@ -30,40 +96,115 @@ impl Grid for GameGridState {
     fn width(&self) -> usize { self.width }
 }
 
+impl GameGridState {
+    pub fn new(width: usize, height: usize) -> Self {
+        let cells = vec![vec![CellState::Empty; width]; height];
+        Self { cells, width, height }
+    }
+
+    /// Copy of the cells of any grid
+    pub fn copy_of<T: Grid + ?Sized>(grid: &T) -> Self {
+        let mut copy = Self::new(grid.width(), grid.height());
+        for row in 0..grid.height() {
+            for col in 0..grid.width() {
+                copy.set(col, row, grid.get(col, row));
+            }
+        }
+        copy
+    }
+}
+
 impl GameState {
     pub fn new(lvl_desc: LevelDescription) -> Self {
-        let width = lvl_desc.cols.len();
-        let height = lvl_desc.rows.len();
-        let cells = vec![vec![CellState::Empty; width]; height];
-        let grid = GameGridState {cells, width, height};
-        let move_queue = Vec::<Transaction>::new();
-        Self { lvl_desc, grid, move_queue}
+        let grid = GameGridState::new(lvl_desc.cols.len(), lvl_desc.rows.len());
+        let history = UndoTree::new();
+        Self { lvl_desc, grid, history, pending_action: None, stats: GameStats::default() }
+    }
+
+    /// Continues a game from a saved grid, undo history and counters
+    pub fn restore(lvl_desc: LevelDescription, grid: GameGridState, history: UndoTree, stats: GameStats) -> Self {
+        let mut result = Self { lvl_desc, grid, history, pending_action: None, stats };
+        for row in 0..result.grid.height() {
+            update_level_description(&result.grid, &mut result.lvl_desc, 0, row);
+        }
+        for col in 0..result.grid.width() {
+            update_level_description(&result.grid, &mut result.lvl_desc, col, 0);
+        }
+        result
+    }
+
+    /// Starts collecting moves into one undo step, a move made outside of an action is a step of its own
+    pub fn begin_action(&mut self) {
+        self.end_action();
+        self.pending_action = Some(Transaction { changes: Vec::new() });
+    }
+
+    pub fn end_action(&mut self) {
+        if let Some(transaction) = self.pending_action.take() {
+            if !transaction.changes.is_empty() {
+                self.history.push(transaction);
+                self.stats.moves += 1;
+            }
+        }
+    }
+
+    /// An action is open, moves are added to its undo step
+    pub fn in_action(&self) -> bool {
+        self.pending_action.is_some()
+    }
+
+    fn record(&mut self, transaction: &Transaction) {
+        match self.pending_action.as_mut() {
+            Some(pending) => pending.merge(transaction),
+            None => {
+                self.history.push(transaction.clone());
+                self.stats.moves += 1;
+            }
+        }
     }
 
     pub fn apply_transaction(&mut self, transaction: &Transaction) {
 
         if transaction.changes.len() > 0 {
+            let own_action = self.pending_action.is_none();
+            if own_action {
+                self.begin_action();
+            }
+
             self.grid.apply_transaction(transaction);
-            self.move_queue.push(transaction.clone());
+            self.record(transaction);
 
             let mut builder = TransactionBuilder::new(&self.grid);
 
             for change in transaction.changes.iter() {
-                if (change.new_state == CellState::Filled || change.new_state == CellState::Empty) {
+                if change.new_state != CellState::Crossed {
                     update_nonogram(&mut builder, &self.lvl_desc, change.col, change.row);
-                    update_level_description(&builder, &mut self.lvl_desc, change.col, change.row);
                 }
             }
 
-            let transaction = builder.to_transaction(&self.grid);
+            let crosses = builder.to_transaction(&self.grid);
 
-            if transaction.changes.len() > 0 {
-                self.grid.apply_transaction(&transaction);
-                self.move_queue.push(transaction);
+            if crosses.changes.len() > 0 {
+                self.grid.apply_transaction(&crosses);
+                self.record(&crosses);
+            }
+
+            // a cross can settle where a block is, so every changed cell updates the clues
+            for change in transaction.changes.iter().chain(crosses.changes.iter()) {
+                update_level_description(&self.grid, &mut self.lvl_desc, change.col, change.row);
+            }
+
+            if own_action {
+                self.end_action();
             }
         }
     }
     pub fn set(&mut self, col: usize, row: usize, val: CellState) {
+        let own_action = self.pending_action.is_none();
+        if own_action {
+            self.begin_action();
+        }
+
         let mut builder = TransactionBuilder::new(&self.grid);
 
         builder.set(col, row, val);
@@ -71,22 +212,28 @@ impl GameState {
         let transaction = builder.to_transaction(&self.grid);
 
         self.grid.apply_transaction(&transaction);
-        self.move_queue.push(transaction);
-
-        if val == CellState::Filled || val == CellState::Empty {
-            let mut builder = TransactionBuilder::new(&self.grid);
+        self.record(&transaction);
 
+        let mut builder = TransactionBuilder::new(&self.grid);
+        if val != CellState::Crossed {
             update_nonogram(&mut builder, &self.lvl_desc, col, row);
-            update_level_description(&builder, &mut self.lvl_desc, col, row);
+        }
+        let crosses = builder.to_transaction(&self.grid);
 
-            let transaction = builder.to_transaction(&self.grid);
+        if crosses.changes.len() > 0 {
+            self.grid.apply_transaction(&crosses);
+            self.record(&crosses);
+        }
 
-            if transaction.changes.len() > 0 {
-                self.grid.apply_transaction(&transaction);
-                self.move_queue.push(transaction);
-            }
+        // a cross can settle where a block is, so the clues are updated for crosses too
+        update_level_description(&self.grid, &mut self.lvl_desc, col, row);
+        for change in crosses.changes.iter() {
+            update_level_description(&self.grid, &mut self.lvl_desc, change.col, change.row);
         }
 
+        if own_action {
+            self.end_action();
+        }
     }
 
     pub fn get(&self, col: usize, row: usize) -> CellState {
@@ -97,17 +244,55 @@ impl GameState {
     pub fn width(&self) -> usize { self.grid.width() }
 
     pub fn undo(&mut self) {
-        let transaction_option = self.move_queue.pop();
+        self.end_action();
+        if self.step_back() {
+            self.stats.undos += 1;
+        }
+    }
+
+    /// Rolls back the current move without counting it as an undo
+    fn step_back(&mut self) -> bool {
+        let transaction_option = self.history.undo();
 
         if let Some(transaction) = transaction_option {
-            self.grid.rollback_transaction(&transaction);
+            self.grid.rollback_transaction(transaction);
+            for change in transaction.changes.iter() {
+                update_level_description(&self.grid, &mut self.lvl_desc, change.col, change.row);
+            }
+            return true;
+        }
+        false
+    }
+
+    pub fn redo(&mut self) {
+        self.end_action();
+        let transaction_option = self.history.redo();
+
+        if let Some(transaction) = transaction_option {
+            self.grid.apply_transaction(transaction);
             for change in transaction.changes.iter() {
                 update_level_description(&self.grid, &mut self.lvl_desc, change.col, change.row);
             }
         }
     }
-    fn set_no_update(&mut self, col: usize, row: usize, val: CellState) {
-        self.grid.set(col, row, val);
+
+    /// Undoes and redoes moves until the grid is in the state of the given history node
+    pub fn jump_to(&mut self, node: NodeId) {
+        self.end_action();
+        if let Some((undo_count, redo_nodes)) = self.history.path_to(node) {
+            // moving around the history is not undoing, the counters stay as they are
+            for _ in 0..undo_count {
+                self.step_back();
+            }
+            for node in redo_nodes {
+                if let Some(transaction) = self.history.redo_to(node) {
+                    self.grid.apply_transaction(transaction);
+                    for change in transaction.changes.iter() {
+                        update_level_description(&self.grid, &mut self.lvl_desc, change.col, change.row);
+                    }
+                }
+            }
+        }
     }
 
     pub fn lvl_desc(&self) -> &LevelDescription {
@@ -116,36 +301,106 @@ impl GameState {
     pub fn grid(&self) -> &GameGridState {
         &self.grid
     }
+    pub fn history(&self) -> &UndoTree {
+        &self.history
+    }
+
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    /// For the counters the game can not see itself: the time and help from the AI
+    pub fn stats_mut(&mut self) -> &mut GameStats {
+        &mut self.stats
+    }
     pub fn grid_to_iter(&self) -> impl Iterator<Item = (usize, usize, CellState)> + '_ {
         self.grid.iter()
     }
 }
 
 pub fn update_level_description<T: Grid>(target: &T, lvl_desc: &mut LevelDescription, col: usize, row: usize) {
-    if lvl_desc.col_to_line_description(col) == line_to_line_description(&target.col_to_line(col)) {
-        for mut i in lvl_desc.cols[col].parts.iter_mut() {
-            i.is_completed = true
-        }
+    let completed = completed_clues(&lvl_desc.col_clues(col), &target.col_to_line(col));
+    for (part, is_completed) in lvl_desc.cols[col].parts.iter_mut().zip(completed) {
+        part.is_completed = is_completed;
     }
-    else {
-        for mut i in lvl_desc.cols[col].parts.iter_mut() {
-            i.is_completed = false
-        }
+
+    let completed = completed_clues(&lvl_desc.row_clues(row), &target.row_to_line(row));
+    for (part, is_completed) in lvl_desc.rows[row].parts.iter_mut().zip(completed) {
+        part.is_completed = is_completed;
+    }
+}
+
+/// Marks every clue whose block is known to be finished.
+/// If the line can still be solved, a block is finished when it is filled and has the same place
+/// in the leftmost and the rightmost packing of the clues. Otherwise only the blocks that
+/// follow an edge through crossed cells and finished blocks can be matched to clues, and
+/// nothing is marked when the line has more blocks than clues or the edges disagree.
+pub fn completed_clues(clues: &[Clue], cells: &[CellState]) -> Vec<bool> {
+    if let (Some(left), Some(right)) = (leftmost_placement(clues, cells), rightmost_placement(clues, cells)) {
+        return clues.iter().enumerate()
+            .map(|(i, (count, color))| left[i] == right[i] && cells[left[i]..left[i] + count].iter().all(|x| *x == CellState::filled(*color)))
+            .collect();
+    }
+
+    let not_completed = vec![false; clues.len()];
+    if min_blocks(cells) > clues.len() {
+        return not_completed;
     }
 
-    if lvl_desc.row_to_line_description(row) == line_to_line_description(&target.row_to_line(row)) {
-        for mut i in lvl_desc.rows[row].parts.iter_mut() {
-            i.is_completed = true
+    let reversed_clues: Vec<Clue> = clues.iter().rev().copied().collect();
+    let reversed_cells: Vec<CellState> = cells.iter().rev().copied().collect();
+    let from_right = anchored_clues(&reversed_clues, &reversed_cells).into_iter().zip(reversed_clues.iter()).rev()
+        .map(|(start, (count, _))| start.map(|x| cells.len() - x - count));
+    let mut starts = Vec::new();
+    for (from_left, from_right) in anchored_clues(clues, cells).into_iter().zip(from_right) {
+        match (from_left, from_right) {
+            (Some(left), Some(right)) if left != right => return not_completed,
+            (left, right) => starts.push(left.or(right))
         }
     }
-    else {
-        for mut i in lvl_desc.rows[row].parts.iter_mut() {
-            i.is_completed = false
+    // a block matched to two clues, or clues matched out of order
+    let matched: Vec<usize> = starts.iter().flatten().copied().collect();
+    if matched.windows(2).any(|x| x[0] >= x[1]) {
+        return not_completed;
+    }
+    starts.iter().map(|x| x.is_some()).collect()
+}
+
+/// Blocks the filled cells make at least: cells of one colour between crosses may still join
+fn min_blocks(cells: &[CellState]) -> usize {
+    cells.split(|x| *x == CellState::Crossed)
+        .map(|segment| {
+            let colors: Vec<ColorId> = segment.iter().filter_map(|x| x.color()).collect();
+            colors.iter().enumerate().filter(|(i, x)| *i == 0 || colors[i - 1] != **x).count()
+        })
+        .sum()
+}
+
+/// Matches blocks to clues from the left edge while only crossed cells lie between them,
+/// every matched clue gets the position where its block starts
+fn anchored_clues(clues: &[Clue], cells: &[CellState]) -> Vec<Option<usize>> {
+    let mut result = vec![None; clues.len()];
+    let mut pos = 0;
+    for ((count, color), block_start) in clues.iter().zip(result.iter_mut()) {
+        while pos < cells.len() && cells[pos] == CellState::Crossed {
+            pos += 1;
         }
+        let start = pos;
+        while pos < cells.len() && cells[pos] == CellState::filled(*color) {
+            pos += 1;
+        }
+        // the block has to be closed by a cross, the edge or a block of another colour
+        let closed = pos == cells.len() || cells[pos] == CellState::Crossed || cells[pos].color().is_some_and(|x| x != *color);
+        if pos == start || !closed || pos - start != *count {
+            break;
+        }
+        *block_start = Some(start);
     }
+    result
 }
+
 pub fn update_nonogram<T: Grid>(target: &mut T, lvl_desc: &LevelDescription, col: usize, row: usize) {
-    if lvl_desc.col_to_line_description(col) == line_to_line_description(&target.col_to_line(col)) {
+    if lvl_desc.col_clues(col) == line_to_clues(&target.col_to_line(col)) {
         for i in 0..target.height() {
             if target.get(col, i) == CellState::Empty {
                 target.set(col, i, CellState::Crossed);
@@ -153,7 +408,7 @@ pub fn update_nonogram<T: Grid>(target: &mut T, lvl_desc: &LevelDescription, col
         }
     }
 
-    if lvl_desc.row_to_line_description(row) == line_to_line_description(&target.row_to_line(row)) {
+    if lvl_desc.row_clues(row) == line_to_clues(&target.row_to_line(row)) {
         for i in 0..target.width() {
             if target.get(i, row) == CellState::Empty {
                 target.set(i, row, CellState::Crossed);
@@ -161,21 +416,51 @@ pub fn update_nonogram<T: Grid>(target: &mut T, lvl_desc: &LevelDescription, col
         }
     }
 }
-pub fn line_to_line_description(line: &Vec<CellState>) -> Vec<usize> {
+/// Checks the filled cells of every line against its clues, it does not rely on the completion marks
+pub fn is_solved<T: Grid + ?Sized>(target: &T, lvl_desc: &LevelDescription) -> bool {
+    (0..target.height()).all(|row| lvl_desc.row_clues(row) == line_to_clues(&target.row_to_line(row)))
+        && (0..target.width()).all(|col| lvl_desc.col_clues(col) == line_to_clues(&target.col_to_line(col)))
+}
+
+/// Filled cells that are empty or of another colour in the solution, as `(col, row)`
+pub fn find_mistakes<T: Grid + ?Sized>(target: &T, solution: &[Vec<Option<ColorId>>]) -> Vec<(usize, usize)> {
     let mut result = Vec::new();
-    let mut buffer = 0;
-    for cell in line.iter() {
-            if *cell != CellState::Filled {
-                if buffer != 0 {
-                    result.push(buffer);
-                    buffer = 0
-                }
-            } else {
-                buffer += 1;
+    for row in 0..target.height() {
+        for col in 0..target.width() {
+            let expected = solution.get(row).and_then(|x| x.get(col)).copied().flatten();
+            if target.get(col, row).color().is_some_and(|x| Some(x) != expected) {
+                result.push((col, row));
             }
+        }
     }
-    if buffer != 0 {
-        result.push(buffer);
+    result
+}
+
+/// Length of the run of cells in the same state as `(col, row)` that contains it, along the row or the column
+pub fn block_length<T: Grid + ?Sized>(target: &T, col: usize, row: usize, horizontal: bool) -> usize {
+    let (line, pos) = if horizontal { (target.row_to_line(row), col) } else { (target.col_to_line(col), row) };
+    let state = line[pos];
+    let before = line[..pos].iter().rev().take_while(|x| **x == state).count();
+    let after = line[pos + 1..].iter().take_while(|x| **x == state).count();
+    before + 1 + after
+}
+
+pub fn line_to_line_description(line: &Vec<CellState>) -> Vec<usize> {
+    line_to_clues(line).into_iter().map(|(count, _)| count).collect()
+}
+
+/// Blocks of the line with their colours, two touching blocks of different colours are two clues
+pub fn line_to_clues(line: &[CellState]) -> Vec<Clue> {
+    let mut result: Vec<Clue> = Vec::new();
+    let mut previous = None;
+    for cell in line.iter() {
+        let color = cell.color();
+        match (color, result.last_mut()) {
+            (Some(color), Some(last)) if previous == Some(color) => last.0 += 1,
+            (Some(color), _) => result.push((1, color)),
+            (None, _) => {}
+        }
+        previous = color;
     }
     result
 }
@@ -183,6 +468,7 @@ pub fn line_to_line_description(line: &Vec<CellState>) -> Vec<usize> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::description::monochrome;
 
     #[test]
     fn test_line_to_description() {
@@ -200,6 +486,168 @@ mod test {
         assert_eq!(result, vec![1,3,1])
     }
 
+    #[test]
+    fn test_line_to_clues_splits_colors() {
+        use CellState::*;
+        let line = vec![Filled, Colored(2), Colored(2), Crossed, Colored(2), Filled, Filled];
+        assert_eq!(line_to_clues(&line), vec![(1, 0), (2, 2), (1, 2), (2, 0)]);
+    }
+
+    #[test]
+    fn test_block_length() {
+        use CellState::*;
+        let mut grid = GameGridState::new(5, 2);
+        for (col, state) in [Filled, Filled, Filled, Crossed, Filled].into_iter().enumerate() {
+            grid.set(col, 0, state);
+        }
+        assert_eq!(block_length(&grid, 1, 0, true), 3);
+        assert_eq!(block_length(&grid, 4, 0, true), 1);
+        assert_eq!(block_length(&grid, 1, 0, false), 1);
+    }
+
+    #[test]
+    fn test_completed_clues() {
+        use CellState::*;
+        // the block of 3 can only be the second clue
+        let line = vec![Empty, Empty, Empty, Filled, Filled, Filled, Empty];
+        assert_eq!(completed_clues(&monochrome(&[1, 3]), &line), vec![false, true]);
+        // the block could be either clue
+        let line = vec![Empty, Empty, Filled, Empty, Empty];
+        assert_eq!(completed_clues(&monochrome(&[1, 1]), &line), vec![false, false]);
+        // a wrong block makes one too many, none of them can be told apart
+        let line = vec![Filled, Crossed, Filled, Crossed, Filled, Empty, Crossed, Filled, Filled];
+        assert_eq!(completed_clues(&monochrome(&[1, 1, 2]), &line), vec![false, false, false]);
+        // the only block is next to both edges, it could be either clue
+        let line = vec![Crossed, Filled, Crossed, Crossed, Crossed];
+        assert_eq!(completed_clues(&monochrome(&[1, 1]), &line), vec![false, false]);
+        // the line cannot be finished, the blocks at the edges are still matched
+        let line = vec![Filled, Crossed, Empty, Filled, Filled, Filled, Empty, Crossed, Filled, Filled];
+        assert_eq!(completed_clues(&monochrome(&[1, 2, 2]), &line), vec![true, false, true]);
+        let line = vec![Filled, Filled, Crossed, Filled, Empty, Filled];
+        assert_eq!(completed_clues(&monochrome(&[1, 2]), &line), vec![false, false]);
+        // blocks of different colours touch
+        let line = vec![Colored(1), Filled, Empty, Colored(1), Crossed];
+        assert_eq!(completed_clues(&[(1, 1), (1, 0), (1, 1)], &line), vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_action_is_one_undo_step() {
+        use crate::description::LevelDescriptionTemplate;
+        use CellState::*;
+        let level = LevelDescriptionTemplate { rows: vec![vec![2], vec![]], cols: vec![vec![1], vec![1]], ..Default::default() };
+        let mut game_state = GameState::new(level.into());
+
+        game_state.begin_action();
+        game_state.set(0, 0, Filled);
+        game_state.set(1, 0, Filled);
+        game_state.end_action();
+        // completed lines are crossed out in the same step
+        assert_eq!(game_state.grid().row_to_line(1), vec![Crossed, Crossed]);
+
+        game_state.undo();
+        assert_eq!(game_state.grid().row_to_line(0), vec![Empty, Empty]);
+        assert_eq!(game_state.grid().row_to_line(1), vec![Empty, Empty]);
+        assert_eq!(game_state.history().current(), crate::history::ROOT);
+        assert_eq!((game_state.stats().moves, game_state.stats().undos), (1, 1));
+    }
+
+    #[test]
+    fn test_ai_move_after_stroke_is_own_undo_step() {
+        use crate::ai_player::AiPlayer;
+        use crate::description::LevelDescriptionTemplate;
+        use crate::strategy::StrategyKind;
+        use crate::transaction::TransactionBuilder;
+        use CellState::*;
+        let level = LevelDescriptionTemplate { rows: vec![vec![], vec![2]], cols: vec![vec![1], vec![1]], ..Default::default() };
+        let mut game_state = GameState::new(level.into());
+        let ai_player = AiPlayer::with_strategies(&[StrategyKind::Simple]);
+        let cells = |game_state: &GameState| -> Vec<CellState> { game_state.grid().iter().map(|(_, _, x)| x).collect() };
+
+        game_state.begin_action();
+        game_state.set(0, 0, Crossed);
+        game_state.set(1, 0, Crossed);
+        // the window lets the AI move only once the stroke is over
+        assert!(game_state.in_action());
+        game_state.end_action();
+        assert!(!game_state.in_action());
+        let after_stroke = cells(&game_state);
+
+        let mut builder = TransactionBuilder::new(game_state.grid());
+        ai_player.play_single_turn_emergency(game_state.lvl_desc(), &mut builder);
+        let transaction = builder.to_transaction(game_state.grid());
+        assert!(!transaction.changes.is_empty());
+        game_state.apply_transaction(&transaction);
+        assert_eq!(game_state.stats().moves, 2);
+
+        game_state.undo();
+        assert_eq!(cells(&game_state), after_stroke);
+        game_state.undo();
+        assert_eq!(cells(&game_state), vec![Empty; 4]);
+    }
+
+    #[test]
+    fn test_cross_completes_a_block() {
+        use crate::description::LevelDescriptionTemplate;
+        use crate::transaction::TransactionBuilder;
+        use CellState::*;
+        let level = LevelDescriptionTemplate {
+            rows: vec![vec![1, 1], vec![]],
+            cols: vec![vec![], vec![], vec![1], vec![], vec![1]],
+            ..Default::default()
+        };
+        let completed = |game_state: &GameState| -> Vec<bool> { game_state.lvl_desc().rows[0].parts.iter().map(|x| x.is_completed).collect() };
+
+        let mut game_state = GameState::new(level.clone().into());
+        game_state.set(2, 0, Filled);
+        assert_eq!(completed(&game_state), vec![false, false]);
+        game_state.set(0, 0, Crossed);
+        game_state.set(1, 0, Crossed);
+        assert_eq!(completed(&game_state), vec![true, false]);
+
+        // the same crosses made by a strategy
+        let mut game_state = GameState::new(level.into());
+        game_state.set(2, 0, Filled);
+        let mut builder = TransactionBuilder::new(game_state.grid());
+        builder.set(0, 0, Crossed);
+        builder.set(1, 0, Crossed);
+        let transaction = builder.to_transaction(game_state.grid());
+        game_state.apply_transaction(&transaction);
+        assert_eq!(completed(&game_state), vec![true, false]);
+    }
+
+    #[test]
+    fn test_jump_to_other_branch() {
+        use crate::description::LevelDescriptionTemplate;
+        use CellState::*;
+        let level = LevelDescriptionTemplate { rows: vec![vec![2], vec![]], cols: vec![vec![1], vec![1]], ..Default::default() };
+        let mut game_state = GameState::new(level.into());
+
+        game_state.set(0, 0, Filled);
+        game_state.set(1, 0, Filled);
+        let solved = game_state.history().current();
+        game_state.undo();
+        game_state.undo();
+        game_state.set(1, 0, Filled);
+        let branch = game_state.history().current();
+        assert_eq!(game_state.stats().undos, 2);
+
+        game_state.jump_to(solved);
+        assert_eq!(game_state.history().current(), solved);
+        assert_eq!(game_state.grid().row_to_line(0), vec![Filled, Filled]);
+        assert_eq!(game_state.grid().row_to_line(1), vec![Crossed, Crossed]);
+        assert!(game_state.lvl_desc().rows[0].parts[0].is_completed);
+        assert!(is_solved(game_state.grid(), game_state.lvl_desc()));
+
+        game_state.jump_to(branch);
+        assert_eq!(game_state.history().current(), branch);
+        assert_eq!(game_state.grid().row_to_line(0), vec![Empty, Filled]);
+        assert_eq!(game_state.grid().row_to_line(1), vec![Empty, Crossed]);
+        assert!(!game_state.lvl_desc().rows[0].parts[0].is_completed);
+        assert!(!game_state.lvl_desc().cols[0].parts[0].is_completed);
+        assert!(game_state.lvl_desc().cols[1].parts[0].is_completed);
+        assert_eq!(game_state.stats().undos, 2);
+    }
+
 }
 
 
@@ -0,0 +1,85 @@
+use std::error::Error;
+
+use nonogram_gamer_rs::description::LevelDescriptionTemplate;
+use nonogram_gamer_rs::format::save_level;
+use nonogram_gamer_rs::generator::{level_from_picture, random_unique_level};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use nonogram_gamer_rs::solver::{Solutions, Solver};
+
+/// Turns a picture into a level, pixels darker than `threshold` become filled cells.
+/// Returns the process exit code, which is non-zero when the uniqueness check fails.
+pub fn from_image(image_path: &str, threshold: u8, output_path: Option<&str>, check_unique: bool) -> Result<i32, Box<dyn Error>> {
+    let image = image::open(image_path)?.to_luma_alpha8();
+    let picture: Vec<Vec<bool>> = image.rows()
+        .map(|row| row.map(|pixel| pixel[1] >= 128 && pixel[0] < threshold).collect())
+        .collect();
+
+    let level = level_from_picture(&picture);
+    write_level(&level, output_path)?;
+
+    if check_unique && !report_uniqueness(&level) {
+        return Ok(1);
+    }
+    Ok(0)
+}
+
+pub struct RandomOptions {
+    pub width: usize,
+    pub height: usize,
+    pub density: f64,
+    pub seed: Option<u64>,
+    pub line_solvable: bool,
+    pub attempts: usize
+}
+
+/// Makes a random level with a unique solution, the same seed always gives the same level.
+/// Returns the process exit code, which is non-zero when no level was found.
+pub fn random(options: &RandomOptions, output_path: Option<&str>) -> Result<i32, Box<dyn Error>> {
+    let seed = options.seed.unwrap_or_else(|| {
+        let seed = rand::random();
+        eprintln!("seed: {}", seed);
+        seed
+    });
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    match random_unique_level(options.width, options.height, options.density, options.line_solvable, options.attempts, &mut rng) {
+        Some(level) => {
+            write_level(&level, output_path)?;
+            Ok(0)
+        },
+        None => {
+            eprintln!("no suitable level found in {} attempts", options.attempts);
+            Ok(1)
+        }
+    }
+}
+
+fn write_level(level: &LevelDescriptionTemplate, output_path: Option<&str>) -> std::io::Result<()> {
+    match output_path {
+        Some(output_path) => save_level(level, output_path),
+        None => {
+            print!("{}", level.to_yaml());
+            Ok(())
+        }
+    }
+}
+
+/// Prints to stderr how many solutions the level has, returns true if it is exactly one
+fn report_uniqueness(level: &LevelDescriptionTemplate) -> bool {
+    let lvl_desc = level.clone().into();
+    match Solver::new(2).solve(&lvl_desc) {
+        Solutions::Unique(_) => {
+            eprintln!("the level has a unique solution");
+            true
+        },
+        Solutions::Multiple(_) => {
+            eprintln!("the level has more than one solution");
+            false
+        },
+        Solutions::None => {
+            eprintln!("the level has no solution");
+            false
+        }
+    }
+}
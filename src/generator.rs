@@ -0,0 +1,90 @@
+use rand::Rng;
+use crate::description::{LevelDescription, LevelDescriptionTemplate, Solution};
+use crate::game_state::{line_to_line_description, CellState, GameGridState};
+use crate::grid::Grid;
+use crate::solver::{Solutions, Solver};
+use crate::strategy::line_solver::propagate;
+
+/// Derives the clues of a picture given as rows of filled (`true`) and empty cells,
+/// the picture is kept as the solution of the level
+pub fn level_from_picture(picture: &[Vec<bool>]) -> LevelDescriptionTemplate {
+    let width = picture.iter().map(|x| x.len()).max().unwrap_or(0);
+    let cell = |row: usize, col: usize| {
+        if picture[row].get(col).copied().unwrap_or(false) { CellState::Filled } else { CellState::Empty }
+    };
+
+    let rows = (0..picture.len())
+        .map(|row| line_to_line_description(&(0..width).map(|col| cell(row, col)).collect()))
+        .collect();
+    let cols = (0..width)
+        .map(|col| line_to_line_description(&(0..picture.len()).map(|row| cell(row, col)).collect()))
+        .collect();
+
+    LevelDescriptionTemplate { rows, cols, solution: Some(Solution::from_cells(picture)), ..Default::default() }
+}
+
+/// Picture where every cell is filled with probability `density`
+pub fn random_picture<R: Rng>(width: usize, height: usize, density: f64, rng: &mut R) -> Vec<Vec<bool>> {
+    (0..height)
+        .map(|_| (0..width).map(|_| rng.gen_bool(density.clamp(0.0, 1.0))).collect())
+        .collect()
+}
+
+/// Draws random pictures until one gives a level with a unique solution,
+/// or one that line solving alone finishes if `line_solvable` is set.
+/// Gives up with `None` after `attempts` pictures.
+pub fn random_unique_level<R: Rng>(
+    width: usize,
+    height: usize,
+    density: f64,
+    line_solvable: bool,
+    attempts: usize,
+    rng: &mut R
+) -> Option<LevelDescriptionTemplate> {
+    for _ in 0..attempts {
+        let level = level_from_picture(&random_picture(width, height, density, rng));
+        let lvl_desc: LevelDescription = level.clone().into();
+        // line solving only makes forced decisions, so a finished grid is the unique solution
+        let is_good = if line_solvable { is_line_solvable(&lvl_desc) } else { has_unique_solution(&lvl_desc) };
+        if is_good {
+            return Some(level);
+        }
+    }
+    None
+}
+
+pub fn is_line_solvable(lvl_desc: &LevelDescription) -> bool {
+    let mut grid = GameGridState::new(lvl_desc.cols.len(), lvl_desc.rows.len());
+    propagate(lvl_desc, &mut grid) && grid.iter().all(|(_, _, cell)| cell != CellState::Empty)
+}
+
+pub fn has_unique_solution(lvl_desc: &LevelDescription) -> bool {
+    matches!(Solver::new(2).solve(lvl_desc), Solutions::Unique(_))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_level_from_picture() {
+        let picture: Vec<Vec<bool>> = [".#.#.", "#####", "#####", ".###.", "..#.."].iter()
+            .map(|x| x.chars().map(|c| c == '#').collect())
+            .collect();
+        let level = level_from_picture(&picture);
+        assert_eq!(level.rows, vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]]);
+        assert_eq!(level.cols, vec![vec![2], vec![4], vec![4], vec![4], vec![2]]);
+    }
+
+    #[test]
+    fn test_random_level_is_reproducible_and_unique() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let first = random_unique_level(8, 6, 0.55, false, 100, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+        let second = random_unique_level(8, 6, 0.55, false, 100, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+        assert_eq!(first.rows, second.rows);
+        assert_eq!(first.cols, second.cols);
+        assert!(has_unique_solution(&first.into()));
+    }
+}
@@ -40,7 +40,7 @@ pub trait Grid {
     fn col_to_line(&self, col: usize) -> Vec<CellState> {
         (0..self.height()).into_iter().map(|i| self.get(col, i)).collect()
     }
-    fn iter(&self) -> GridIterator<Self> where Self: Sized {
+    fn iter(&self) -> GridIterator<'_, Self> where Self: Sized {
         GridIterator {
             target: self,
             first: true,
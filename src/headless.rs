@@ -0,0 +1,93 @@
+use nonogram_gamer_rs::ai_player::AiPlayer;
+use nonogram_gamer_rs::difficulty::{analyze, DifficultyReport};
+use nonogram_gamer_rs::format::load_level;
+use nonogram_gamer_rs::game_state::{color_symbol, is_solved, CellState, GameState};
+use nonogram_gamer_rs::grid::Grid;
+use nonogram_gamer_rs::strategy::StrategyKind;
+use nonogram_gamer_rs::strategy::line_solver::propagate;
+use nonogram_gamer_rs::transaction::TransactionBuilder;
+use nonogram_gamer_rs::validation::LevelError;
+
+pub const EXIT_SOLVED: i32 = 0;
+pub const EXIT_UNSOLVED: i32 = 1;
+pub const EXIT_CONTRADICTION: i32 = 2;
+
+/// Runs the strategies on the level until none of them can make a move,
+/// prints the grid to stdout and returns the process exit code.
+pub fn solve(level_path: &str, strategies: &[StrategyKind]) -> Result<i32, LevelError> {
+    let lvl_desc = load_level(level_path)?;
+    let mut game_state = GameState::new(lvl_desc.into());
+    let ai_player = AiPlayer::with_strategies(strategies);
+
+    loop {
+        let mut builder = TransactionBuilder::new(game_state.grid());
+        ai_player.play_single_turn_emergency(game_state.lvl_desc(), &mut builder);
+        let transaction = builder.to_transaction(game_state.grid());
+        if transaction.changes.is_empty() {
+            break;
+        }
+        game_state.apply_transaction(&transaction);
+    }
+
+    print!("{}", grid_to_ascii(game_state.grid()));
+
+    if is_solved(game_state.grid(), game_state.lvl_desc()) {
+        Ok(EXIT_SOLVED)
+    }
+    else if !propagate(game_state.lvl_desc(), &mut TransactionBuilder::new(game_state.grid())) {
+        eprintln!("{}: the puzzle is contradictory", level_path);
+        Ok(EXIT_CONTRADICTION)
+    }
+    else {
+        eprintln!("{}: strategies {} could not finish the puzzle", level_path, strategies.iter().map(|x| x.name()).collect::<Vec<_>>().join(", "));
+        Ok(EXIT_UNSOLVED)
+    }
+}
+
+/// One line per row: `#` for filled, `1`-`9` and `A`-`Z` for other colours, `.` for crossed and `?` for undecided cells
+pub fn grid_to_ascii<T: Grid + ?Sized>(grid: &T) -> String {
+    let mut result = String::new();
+    for row in 0..grid.height() {
+        for cell in grid.row_to_line(row) {
+            result.push(match cell {
+                CellState::Filled => '#',
+                CellState::Colored(color) => color_symbol(color),
+                CellState::Crossed => '.',
+                CellState::Empty => '?'
+            });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Rates every level and prints them easiest first, levels that can not be read are reported and skipped.
+pub fn rate(level_paths: &[String], show_trace: bool) -> i32 {
+    let mut code = 0;
+    let mut reports: Vec<(&String, DifficultyReport)> = Vec::new();
+    for level_path in level_paths {
+        match load_level(level_path) {
+            Ok(lvl_desc) => reports.push((level_path, analyze(lvl_desc.into()))),
+            Err(e) => {
+                eprintln!("{}: {}", level_path, e);
+                code = 1;
+            }
+        }
+    }
+    reports.sort_by_key(|(_, report)| (report.difficulty(), report.score()));
+
+    for (level_path, report) in reports {
+        let steps: Vec<String> = StrategyKind::ALL.iter()
+            .map(|x| (x, report.steps_of(*x)))
+            .filter(|(_, count)| *count > 0)
+            .map(|(x, count)| format!("{}={}", x, count))
+            .collect();
+        println!("{:<10} {:>5}  {}  [{}]", report.difficulty().name(), report.score(), level_path, steps.join(" "));
+        if show_trace {
+            for (i, step) in report.trace.iter().enumerate() {
+                println!("    {:>3}. {} decided {} cells", i + 1, step.strategy, step.cells_changed);
+            }
+        }
+    }
+    code
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use crate::transaction::Transaction;
+
+pub type NodeId = usize;
+
+pub const ROOT: NodeId = 0;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryNode {
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+    /// child that `redo` goes to, the one visited last
+    pub redo_child: Option<NodeId>,
+    /// leads from the parent to this node, empty for the root
+    pub transaction: Transaction
+}
+
+/// Undo history where undoing and then making a new move starts a new branch
+/// instead of throwing the undone moves away.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UndoTree {
+    nodes: Vec<HistoryNode>,
+    current: NodeId
+}
+
+impl Default for UndoTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UndoTree {
+    pub fn new() -> Self {
+        let root = HistoryNode { parent: None, children: Vec::new(), redo_child: None, transaction: Transaction { changes: Vec::new() } };
+        Self { nodes: vec![root], current: ROOT }
+    }
+
+    pub fn current(&self) -> NodeId { self.current }
+    pub fn nodes(&self) -> &[HistoryNode] { &self.nodes }
+
+    /// Adds the move as a new child of the current node and moves to it
+    pub fn push(&mut self, transaction: Transaction) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(HistoryNode { parent: Some(self.current), children: Vec::new(), redo_child: None, transaction });
+        self.nodes[self.current].children.push(id);
+        self.nodes[self.current].redo_child = Some(id);
+        self.current = id;
+        id
+    }
+
+    /// Moves to the parent, returns the move to roll back
+    pub fn undo(&mut self) -> Option<&Transaction> {
+        let node = &self.nodes[self.current];
+        let parent = node.parent?;
+        let id = self.current;
+        self.current = parent;
+        Some(&self.nodes[id].transaction)
+    }
+
+    /// Moves to the child visited last, returns the move to apply again
+    pub fn redo(&mut self) -> Option<&Transaction> {
+        let child = self.nodes[self.current].redo_child?;
+        self.redo_to(child)
+    }
+
+    /// Moves to the given child of the current node, returns the move to apply again
+    pub fn redo_to(&mut self, child: NodeId) -> Option<&Transaction> {
+        if self.nodes.get(child)?.parent != Some(self.current) {
+            return None;
+        }
+        self.nodes[self.current].redo_child = Some(child);
+        self.current = child;
+        Some(&self.nodes[child].transaction)
+    }
+
+    /// Number of undos and the nodes to redo, in order, that lead from the current node to `target`
+    pub fn path_to(&self, target: NodeId) -> Option<(usize, Vec<NodeId>)> {
+        let from = self.ancestors(self.current);
+        let mut to = self.ancestors(target);
+        if to.is_empty() {
+            return None;
+        }
+
+        // both lists end with the root, drop the common tail
+        let mut undo_count = from.len();
+        while undo_count > 0 && to.last() == from.get(undo_count - 1) {
+            undo_count -= 1;
+            to.pop();
+        }
+        to.reverse();
+        Some((undo_count, to))
+    }
+
+    /// The node itself and all its parents up to the root, empty for an unknown node
+    fn ancestors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut result = Vec::new();
+        let mut node = if node < self.nodes.len() { Some(node) } else { None };
+        while let Some(id) = node {
+            result.push(id);
+            node = self.nodes[id].parent;
+        }
+        result
+    }
+
+    /// Checks that the links between nodes are consistent, for trees read from a file
+    pub fn is_well_formed(&self) -> bool {
+        let count = self.nodes.len();
+        count > 0 && self.current < count && self.nodes[ROOT].parent.is_none()
+            && self.nodes.iter().enumerate().all(|(id, node)| {
+                let parent_ok = id == ROOT || node.parent.is_some_and(|x| x < id && self.nodes[x].children.contains(&id));
+                let children_ok = node.children.iter().all(|x| *x < count && self.nodes[*x].parent == Some(id));
+                let redo_ok = node.redo_child.is_none_or(|x| node.children.contains(&x));
+                parent_ok && children_ok && redo_ok
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::CellState;
+    use crate::transaction::TransactionDetails;
+
+    fn fill(col: usize) -> Transaction {
+        Transaction { changes: vec![TransactionDetails { col, row: 0, old_state: CellState::Empty, new_state: CellState::Filled }] }
+    }
+
+    #[test]
+    fn test_branches_are_kept() {
+        let mut tree = UndoTree::new();
+        let first = tree.push(fill(0));
+        let second = tree.push(fill(1));
+        assert_eq!(tree.undo().unwrap().changes[0].col, 1);
+
+        let other = tree.push(fill(2));
+        assert_eq!(tree.nodes()[first].children, vec![second, other]);
+
+        tree.undo();
+        assert_eq!(tree.redo().unwrap().changes[0].col, 2);
+        assert_eq!(tree.path_to(second), Some((1, vec![second])));
+        assert_eq!(tree.path_to(ROOT), Some((2, vec![])));
+        assert!(tree.is_well_formed());
+    }
+}
@@ -0,0 +1,280 @@
+use ggez::glam::Vec2;
+use ggez::winit::dpi::LogicalSize;
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Color, Rect, TextLayout};
+use ggez::event::{EventHandler, MouseButton};
+use ggez::input::keyboard::{KeyCode, KeyInput};
+
+use nonogram_gamer_rs::difficulty::{BackgroundRating, Difficulty};
+use nonogram_gamer_rs::pack::{self, PackEntry, Progress};
+use nonogram_gamer_rs::profile::{self, Profile};
+
+use crate::app::Transition;
+use crate::my_game::MAIN_FONT;
+
+const WINDOW_SIZE: Vec2 = Vec2::new(900.0, 700.0);
+const ROW_HEIGHT: f32 = 40.0;
+const TEXT_SCALE: f32 = 24.0;
+const MARGIN: f32 = 20.0;
+
+/// Lists the levels of a directory, clicking one or pressing Enter plays it.
+/// The menu is kept while a level is played, levels are rated only once.
+pub struct LevelMenu {
+    levels_dir: String,
+    entries: Vec<PackEntry>,
+    /// difficulties of the levels, filled in while the menu is shown
+    rating: BackgroundRating,
+    selected: usize,
+    /// index of the first entry on screen
+    scroll: usize,
+    window: Vec2,
+    /// shown at the bottom until another level is opened
+    pub error: Option<String>,
+    pub transition: Option<Transition>
+}
+
+impl LevelMenu {
+    pub fn new(ctx: &mut Context, levels_dir: &str, profile_path: &str) -> Self {
+        Self::show(ctx);
+        let (profile, profile_error) = match Profile::from_file(profile_path) {
+            Ok(profile) => (profile, None),
+            Err(e) => (Profile::default(), Some(format!("{}: {}", profile_path, e)))
+        };
+        let (entries, error) = match pack::scan(levels_dir, &profile) {
+            Ok(entries) if entries.is_empty() => (entries, Some(format!("no levels in {}", levels_dir))),
+            Ok(entries) => (entries, profile_error),
+            Err(e) => (Vec::new(), Some(format!("can not read {}: {}", levels_dir, e)))
+        };
+        let levels = entries.iter()
+            .filter_map(|x| x.summary.as_ref().ok().map(|y| (x.path.clone(), y.level.clone())))
+            .collect();
+        Self {
+            levels_dir: levels_dir.to_string(),
+            entries,
+            rating: BackgroundRating::start(levels),
+            selected: 0,
+            scroll: 0,
+            window: WINDOW_SIZE,
+            error,
+            transition: None
+        }
+    }
+
+    /// Gives the window back its size and title, after a level was played in it
+    pub fn show(ctx: &mut Context) {
+        ctx.gfx.window().set_inner_size(LogicalSize::new(WINDOW_SIZE.x, WINDOW_SIZE.y));
+        ctx.gfx.set_window_title("Nonogram-gamer");
+    }
+
+    /// Reads the progress of the levels again, the last one played may have changed
+    pub fn refresh(&mut self, profile_path: &str) {
+        match Profile::from_file(profile_path) {
+            Ok(profile) => pack::refresh(&mut self.entries, &profile),
+            Err(e) => self.error = Some(format!("{}: {}", profile_path, e))
+        }
+    }
+
+    /// Title line and column names take two rows, the status line one
+    fn visible_rows(&self) -> usize {
+        ((self.window.y - 3.0 * ROW_HEIGHT) / ROW_HEIGHT).floor().max(1.0) as usize
+    }
+
+    fn entry_rect(&self, index: usize) -> Rect {
+        let y = (2 + index - self.scroll) as f32 * ROW_HEIGHT;
+        Rect::new(0.0, y, self.window.x, ROW_HEIGHT)
+    }
+
+    fn entry_at(&self, y: f32) -> Option<usize> {
+        let row = (y / ROW_HEIGHT).floor() as isize - 2;
+        if row < 0 || row as usize >= self.visible_rows() {
+            return None;
+        }
+        let index = self.scroll + row as usize;
+        if index < self.entries.len() { Some(index) } else { None }
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        self.selected = index.min(self.entries.len() - 1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        }
+        else if self.selected >= self.scroll + self.visible_rows() {
+            self.scroll = self.selected + 1 - self.visible_rows();
+        }
+    }
+
+    fn scroll_by(&mut self, rows: isize) {
+        let max_scroll = self.entries.len().saturating_sub(self.visible_rows());
+        self.scroll = self.scroll.saturating_add_signed(rows).min(max_scroll);
+    }
+
+    fn open(&mut self, index: usize) {
+        match self.entries.get(index).map(|x| (&x.path, &x.summary)) {
+            Some((path, Ok(_))) => self.transition = Some(Transition::OpenLevel(path.clone())),
+            Some((path, Err(e))) => self.error = Some(format!("{}: {}", path, e)),
+            None => {}
+        }
+    }
+
+    /// Difficulty of a level, `None` while it is being rated
+    pub fn difficulty(&self, path: &str) -> Option<Difficulty> {
+        self.entries.iter()
+            .find(|x| x.path == path)
+            .and_then(|x| x.summary.as_ref().ok())
+            .and_then(|x| x.difficulty)
+    }
+
+    /// Columns: title, size, difficulty, progress, and time, moves, undos and AI help of the best game,
+    /// from the right edge of the window
+    fn columns(&self) -> [f32; 8] {
+        let right = self.window.x;
+        [MARGIN, right - 720.0, right - 630.0, right - 475.0, right - 350.0, right - 240.0, right - 150.0, right - 60.0]
+    }
+}
+
+fn text(value: &str) -> graphics::Text {
+    graphics::Text::new(value)
+        .set_font(MAIN_FONT)
+        .set_layout(TextLayout::top_left())
+        .set_scale(TEXT_SCALE)
+        .clone()
+}
+
+/// Cuts the text to what fits in `width` pixels of the monospace font
+fn fit(value: &str, width: f32) -> String {
+    let max_chars = (width / (TEXT_SCALE * 0.6)).max(1.0) as usize;
+    if value.chars().count() <= max_chars {
+        value.to_string()
+    }
+    else {
+        value.chars().take(max_chars.saturating_sub(1)).chain(std::iter::once('~')).collect()
+    }
+}
+
+fn progress_color(progress: Progress) -> Color {
+    match progress {
+        Progress::New => Color::from_rgb(127, 127, 127),
+        Progress::Started => Color::from_rgb(230, 150, 0),
+        Progress::Solved => Color::from_rgb(0, 200, 83)
+    }
+}
+
+impl EventHandler for LevelMenu {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        for (path, difficulty) in self.rating.finished() {
+            if let Some(Ok(summary)) = self.entries.iter_mut().find(|x| x.path == path).map(|x| x.summary.as_mut()) {
+                summary.difficulty = Some(difficulty);
+            }
+        }
+
+        let pos = ctx.mouse.position();
+        // opened on release, a button still held down would paint on the board
+        if ctx.mouse.button_just_released(MouseButton::Left) {
+            if let Some(index) = self.entry_at(pos.y) {
+                self.selected = index;
+                self.open(index);
+            }
+        }
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeated: bool) -> GameResult {
+        let page = self.visible_rows();
+        match input.keycode {
+            Some(KeyCode::Up) | Some(KeyCode::K) => self.select(self.selected.saturating_sub(1)),
+            Some(KeyCode::Down) | Some(KeyCode::J) => self.select(self.selected + 1),
+            Some(KeyCode::PageUp) => self.select(self.selected.saturating_sub(page)),
+            Some(KeyCode::PageDown) => self.select(self.selected + page),
+            Some(KeyCode::Home) => self.select(0),
+            Some(KeyCode::End) => self.select(self.entries.len().saturating_sub(1)),
+            Some(KeyCode::Return) | Some(KeyCode::NumpadEnter) | Some(KeyCode::Space) => self.open(self.selected),
+            Some(KeyCode::Escape) => ctx.request_quit(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+        self.scroll_by(-y.round() as isize);
+        Ok(())
+    }
+
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        self.window = Vec2::new(width, height);
+        self.select(self.selected);
+        self.scroll_by(0);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, Color::WHITE);
+        let grey = Color::from_rgb(127, 127, 127);
+        let [title_x, size_x, difficulty_x, progress_x, best_x, moves_x, undos_x, ai_x] = self.columns();
+        let text_offset = (ROW_HEIGHT - TEXT_SCALE) / 2.0;
+
+        let header = format!("Levels in {}", self.levels_dir);
+        canvas.draw(&text(&fit(&header, self.window.x - 2.0 * MARGIN)), graphics::DrawParam::from(Vec2::new(MARGIN, text_offset)).color(Color::BLACK));
+        for (x, name) in [(title_x, "title"), (size_x, "size"), (difficulty_x, "difficulty"), (progress_x, "progress"), (best_x, "best"), (moves_x, "moves"), (undos_x, "undos"), (ai_x, "AI")] {
+            canvas.draw(&text(name), graphics::DrawParam::from(Vec2::new(x, ROW_HEIGHT + text_offset)).color(grey));
+        }
+
+        let hovered = self.entry_at(ctx.mouse.position().y);
+        let visible = self.scroll..(self.scroll + self.visible_rows()).min(self.entries.len());
+        for index in visible {
+            let rect = self.entry_rect(index);
+            let highlight = if index == self.selected {
+                Some(Color::from_rgba(41, 121, 255, 80))
+            }
+            else if Some(index) == hovered {
+                Some(Color::from_rgba(41, 121, 255, 40))
+            }
+            else {
+                None
+            };
+            if let Some(color) = highlight {
+                canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(rect).color(color));
+            }
+
+            let y = rect.y + text_offset;
+            let entry = &self.entries[index];
+            match &entry.summary {
+                Ok(summary) => {
+                    canvas.draw(&text(&fit(summary.shown_title(), size_x - title_x - MARGIN)), graphics::DrawParam::from(Vec2::new(title_x, y)).color(Color::BLACK));
+                    canvas.draw(&text(&format!("{}x{}", summary.width, summary.height)), graphics::DrawParam::from(Vec2::new(size_x, y)).color(Color::BLACK));
+                    match summary.difficulty {
+                        Some(difficulty) => canvas.draw(&text(difficulty.name()), graphics::DrawParam::from(Vec2::new(difficulty_x, y)).color(Color::BLACK)),
+                        None => canvas.draw(&text("rating..."), graphics::DrawParam::from(Vec2::new(difficulty_x, y)).color(grey))
+                    }
+                    canvas.draw(&text(summary.progress.name()), graphics::DrawParam::from(Vec2::new(progress_x, y)).color(progress_color(summary.progress)));
+                    if let Some(record) = summary.record.filter(|x| x.is_solved()) {
+                        let best = record.best;
+                        let cells = [
+                            (best_x, profile::format_time(best.seconds)),
+                            (moves_x, best.moves.to_string()),
+                            (undos_x, best.undos.to_string()),
+                            (ai_x, if best.ai_helped { "yes" } else { "no" }.to_string())
+                        ];
+                        for (x, value) in cells {
+                            canvas.draw(&text(&value), graphics::DrawParam::from(Vec2::new(x, y)).color(Color::BLACK));
+                        }
+                    }
+                },
+                Err(_) => {
+                    let name = format!("{} (broken)", entry.path);
+                    canvas.draw(&text(&fit(&name, self.window.x - 2.0 * MARGIN)), graphics::DrawParam::from(Vec2::new(title_x, y)).color(grey));
+                }
+            }
+        }
+
+        let status_y = self.window.y - ROW_HEIGHT;
+        let (status, color) = match &self.error {
+            Some(error) => (error.as_str(), Color::from_rgb(255, 23, 68)),
+            None => ("Enter or click to play, Escape to quit", grey)
+        };
+        canvas.draw(&text(&fit(status, self.window.x - 2.0 * MARGIN)), graphics::DrawParam::from(Vec2::new(MARGIN, status_y + text_offset)).color(color));
+        canvas.finish(ctx)
+    }
+}
@@ -0,0 +1,18 @@
+
+pub mod game_state;
+pub mod description;
+pub mod validation;
+pub mod format;
+pub mod grid;
+
+pub mod transaction;
+pub mod history;
+pub mod strategy;
+pub mod ai_player;
+pub mod line;
+pub mod solver;
+pub mod generator;
+pub mod difficulty;
+pub mod save;
+pub mod profile;
+pub mod pack;
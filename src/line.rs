@@ -1,5 +1,5 @@
 use crate::game_state::CellState;
-use crate::grid::{Grid, GridIterator};
+use crate::grid::Grid;
 
 pub struct LineIterator<'a, T: Line> {
     target: &'a T,
@@ -9,7 +9,7 @@ pub struct LineIterator<'a, T: Line> {
 impl<'a, T: Line> Iterator for LineIterator<'a, T> {
     type Item = CellState;
     fn next(&mut self) -> Option<Self::Item> {
-        if (self.pos < self.target.len()) {
+        if self.pos < self.target.len() {
             let res = Some(self.target.get(self.pos));
             self.pos += 1;
             res
@@ -23,7 +23,8 @@ pub trait Line {
     fn get(&self, pos: usize) -> CellState;
     fn set(&mut self, pos: usize, cell_state: CellState);
     fn len(&self) -> usize;
-    fn iter(&self) -> LineIterator<Self> where Self: Sized {
+    fn is_empty(&self) -> bool { self.len() == 0 }
+    fn iter(&self) -> LineIterator<'_, Self> where Self: Sized {
         LineIterator {
             pos: 0,
             target: &self
@@ -31,12 +32,12 @@ pub trait Line {
     }
 }
 
-pub struct RowLine<'a, T : Grid> {
+pub struct RowLine<'a, T : Grid + ?Sized> {
     target: &'a mut T,
     row_num: usize
 }
 
-impl<'a, T: Grid> RowLine<'a, T> {
+impl<'a, T: Grid + ?Sized> RowLine<'a, T> {
     pub fn new(tagret: &'a mut T, row_num: usize) -> Self {
         Self {
             target: tagret,
@@ -45,7 +46,7 @@ impl<'a, T: Grid> RowLine<'a, T> {
     }
 }
 
-impl<'a, T: Grid> Line for RowLine<'a, T> {
+impl<'a, T: Grid + ?Sized> Line for RowLine<'a, T> {
     fn get(&self, pos: usize) -> CellState { self.target.get(pos, self.row_num) }
 
     fn set(&mut self, pos: usize, cell_state: CellState) { self.target.set(pos, self.row_num, cell_state) }
@@ -53,12 +54,12 @@ impl<'a, T: Grid> Line for RowLine<'a, T> {
     fn len(&self) -> usize { self.target.width() }
 }
 
-pub struct ColLine<'a, T : Grid> {
+pub struct ColLine<'a, T : Grid + ?Sized> {
     target: &'a mut T,
     col_num: usize
 }
 
-impl<'a, T: Grid> ColLine<'a, T> {
+impl<'a, T: Grid + ?Sized> ColLine<'a, T> {
     pub fn new(target: &'a mut T, col_num: usize) -> Self {
         Self {
             target: target,
@@ -67,7 +68,7 @@ impl<'a, T: Grid> ColLine<'a, T> {
     }
 }
 
-impl<'a, T: Grid> Line for ColLine<'a, T> {
+impl<'a, T: Grid + ?Sized> Line for ColLine<'a, T> {
     fn get(&self, pos: usize) -> CellState { self.target.get(self.col_num, pos) }
 
     fn set(&mut self, pos: usize, cell_state: CellState) { self.target.set(self.col_num, pos, cell_state) }
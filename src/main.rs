@@ -1,491 +1,172 @@
-#![allow(unused)]
-
-mod game_state;
-mod description;
+#[cfg(feature = "gui")]
+mod app;
+#[cfg(feature = "gui")]
+mod board_view;
+#[cfg(feature = "gui")]
 mod clickable_zone;
-mod grid;
-
+#[cfg(feature = "gui")]
 mod meshes;
-mod transaction;
-mod strategy;
-mod ai_player;
-mod line;
-
-use std::cell::Cell;
-use std::path;
-
-use clap::Parser;
-use description::LevelDescriptionTemplate;
-use ggez::glam::Vec2;
-use ggez::winit::dpi::{Size, PhysicalSize, LogicalSize};
-use ggez::{Context, ContextBuilder, GameResult, GameError, mint};
-use ggez::graphics::{self, Color, Text, TextFragment, PxScale, TextLayout, Rect, Canvas};
-use ggez::event::{self, EventHandler, MouseButton};
-use ggez::mint::{Point2, Vector2};
-use serde::{Serialize, Deserialize};
-
-use crate::ai_player::AiPlayer;
-use crate::game_state::{CellState, GameState};
-use crate::clickable_zone::ClickableZone;
-use crate::game_state::CellState::{Crossed, Empty, Filled};
-use crate::transaction::TransactionBuilder;
-use crate::strategy::simple::SimpleStrategy;
-
-
-const CELL_SIZE: f32 = 100.0;
-const MAIN_FONT: &'static str = "LiberationMono";
+#[cfg(feature = "gui")]
+mod level_menu;
+#[cfg(feature = "gui")]
+mod my_game;
+mod headless;
+mod generate;
+
+use clap::{ArgGroup, Parser, Subcommand};
+use nonogram_gamer_rs::description::LevelDescriptionTemplate;
+use nonogram_gamer_rs::format::{load_level, save_level};
+use nonogram_gamer_rs::game_state::GameState;
+use nonogram_gamer_rs::profile::default_profile_path;
+use nonogram_gamer_rs::save::{default_save_path, SaveGame};
+use nonogram_gamer_rs::strategy::StrategyKind;
 
 #[derive(Parser)]
-#[command()]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    pub level_path: String
-}
-
-fn main() -> GameResult {
-    let cli = Cli::parse();
-    let lvl_desc = LevelDescriptionTemplate::from_file(&cli.level_path)?;
-
-    let resource_dir = if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
-        let mut path = path::PathBuf::from(manifest_dir);
-        path.push("resources");
-        path
-    } else {
-        path::PathBuf::from("./resources")
-    };
-    // Make a Context.
-    let (mut ctx, event_loop) = ContextBuilder::new("Nonogram-gamer", "Alexei + Dmitri")
-        .add_resource_path(resource_dir)
-        .build()?;
-
-    let my_game = MyGame::new(&mut ctx, lvl_desc);
-
-    // Run!
-    event::run(ctx, event_loop, my_game);
-}
-
-struct GameClickState {
-    state: CellState,
-    col: usize,
-    row: usize,
-    is_horizontal: bool,
-    is_vertical: bool
+    /// Level to play, or a directory of levels to pick one from
+    #[arg(required_unless_present = "resume")]
+    pub level_path: Option<String>,
+    /// Continue the game stored in this save file, progress is saved back to it on quit
+    #[arg(long)]
+    pub resume: Option<String>,
+    /// File with the records of solved levels, `~/.nonogram-profile.yaml` by default
+    #[arg(long)]
+    pub profile: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>
 }
 
-struct MyGame {
-    max_nums_in_rows: usize,
-    max_nums_in_cols: usize,
-    background_mesh: graphics::Mesh,
-    cross_mesh: graphics::Mesh,
-    transparent_cross_mesh: graphics::Mesh,
-    game_state: GameState,
-    game_zone: ClickableZone,
-    undo_zone: ClickableZone,
-    click_state: Option<GameClickState>,
-
-    play_once_zone: ClickableZone,
-    play_many_zone: ClickableZone,
-    pause_zone: ClickableZone,
-    ai_player: AiPlayer,
-
-    done_mesh: graphics::Mesh,
-    stopped_mesh: graphics::Mesh,
-    in_progress_mesh: graphics::Mesh
-}
-
-pub fn cell_num_to_coord(shift_in_cells: usize) -> f32 {
-    shift_in_cells as f32 * CELL_SIZE
-}
-
-impl MyGame {
-    pub fn new(ctx: &mut Context, lvl_desc: LevelDescriptionTemplate) -> MyGame {
-        // Eager evaluation of screen size and margins
-        let max_nums_in_rows = lvl_desc.rows.iter().map(|r| r.len()).max().unwrap_or(0);
-        let max_nums_in_cols = lvl_desc.cols.iter().map(|r| r.len()).max().unwrap_or(0);
-        let screen_size = LogicalSize::new(
-            cell_num_to_coord(max_nums_in_rows + lvl_desc.cols.len()) + 2.0,
-            cell_num_to_coord(max_nums_in_cols + lvl_desc.rows.len()) + 2.0,
-        );
-
-        let screen_size_with_buttons_line = LogicalSize::new(
-            cell_num_to_coord(max_nums_in_rows + lvl_desc.cols.len()) + 2.0,
-            cell_num_to_coord(max_nums_in_cols + lvl_desc.rows.len() + 1) + 2.0,
-        );
-
-        ctx.gfx.window().set_inner_size(screen_size_with_buttons_line);
-        // Load fonts
-        ctx.gfx.add_font(MAIN_FONT, graphics::FontData::from_path(ctx, "/LiberationMono-Regular.ttf").unwrap());
-        // Prepare background
-        let mb = &mut graphics::MeshBuilder::new();
-        mb.rectangle(
-            graphics::DrawMode::stroke(4.0),
-            graphics::Rect::new(
-                cell_num_to_coord(max_nums_in_rows), 
-                cell_num_to_coord(max_nums_in_cols), 
-                cell_num_to_coord(lvl_desc.cols.len()), 
-                cell_num_to_coord(lvl_desc.rows.len())),
-            graphics::Color::BLACK
-        );
-        for i in 1..lvl_desc.rows.len() {
-            let h = cell_num_to_coord(max_nums_in_cols + i);
-            mb.line(&[Vec2::new(0.0, h), Vec2::new(screen_size.width, h)], 2.0, Color::from_rgb(50, 99, 168));
-        }
-        for i in 1..lvl_desc.cols.len() {
-            let w = cell_num_to_coord(max_nums_in_rows + i);
-            mb.line(&[Vec2::new(w, 0.0), Vec2::new(w, screen_size.height)], 2.0, Color::from_rgb(50, 99, 168));
-        }
-        let background_mesh = graphics::Mesh::from_data(ctx, mb.build());
-        // prepare cross texture
-        let cross_mesh = meshes::cross(0.05, Color::from_rgb(100, 100, 100), &ctx);
-        let transparent_cross_mesh = meshes::cross(0.05, Color::from_rgba(100, 100, 100, 100), &ctx);
-        let game_state = game_state::GameState::new(lvl_desc.into());
-
-        let x_offset = cell_num_to_coord(max_nums_in_rows);
-        let y_offset = cell_num_to_coord(max_nums_in_cols);
-
-        let game_zone = ClickableZone::new(
-            Point2::<f32>::from([x_offset, y_offset]),
-            Vector2::<f32>::from([cell_num_to_coord(game_state.width()), cell_num_to_coord(game_state.height())])
-        );
-
-
-
-        let default_button_color = Color::from_rgb(0, 0, 0);
-        let default_button_hover_color = Color::from_rgb(127, 127, 127);
-        let width = 0.02;
-
-        let mut undo_zone = ClickableZone::new(
-            Point2::<f32>::from([
-                cell_num_to_coord(0),
-                cell_num_to_coord(max_nums_in_cols + game_state.height())
-            ]),
-            Vector2::<f32>::from([CELL_SIZE, CELL_SIZE]),
-        );
-        undo_zone.set_mesh_for_draw(meshes::left_arrow(width, default_button_color, &ctx));
-        undo_zone.set_mesh_for_draw_at_hover(meshes::left_arrow(width, default_button_hover_color, &ctx));
-
-        let mut play_once_zone = ClickableZone::new(
-            Point2::<f32>::from([
-                cell_num_to_coord(2),
-                cell_num_to_coord(max_nums_in_cols + game_state.height())
-            ]),
-            Vector2::<f32>::from([CELL_SIZE, CELL_SIZE]),
-        );
-        play_once_zone.set_mesh_for_draw(meshes::play_once(width, default_button_color, &ctx));
-        play_once_zone.set_mesh_for_draw_at_hover(meshes::play_once(width, default_button_hover_color, &ctx));
-
-        let mut play_many_zone = ClickableZone::new(
-            Point2::<f32>::from([
-                cell_num_to_coord(3),
-                cell_num_to_coord(max_nums_in_cols + game_state.height())
-            ]),
-            Vector2::<f32>::from([CELL_SIZE, CELL_SIZE]),
-        );
-        play_many_zone.set_mesh_for_draw(meshes::play_many(width, default_button_color, &ctx));
-        play_many_zone.set_mesh_for_draw_at_hover(meshes::play_many(width, default_button_hover_color, &ctx));
-
-        let mut pause_zone = ClickableZone::new(
-            Point2::<f32>::from([
-                cell_num_to_coord(4),
-                cell_num_to_coord(max_nums_in_cols + game_state.height())
-            ]),
-            Vector2::<f32>::from([CELL_SIZE, CELL_SIZE]),
-        );
-        pause_zone.set_mesh_for_draw(meshes::pause(width, default_button_color, &ctx));
-        pause_zone.set_mesh_for_draw_at_hover(meshes::pause(width, default_button_hover_color, &ctx));
-
-        let mut ai_player = AiPlayer::new();
-        ai_player.engines.push(Box::new( SimpleStrategy {}));
-
-        let done_mesh = meshes::done(0.02, Color::from_rgb(0, 200, 83), &ctx);
-        let stopped_mesh = meshes::stopped(0.02, Color::from_rgb(255, 23, 68), &ctx);
-        let in_progress_mesh = meshes::in_progress(0.02, Color::from_rgb(254, 223, 88), &ctx);
-
-        MyGame {
-            max_nums_in_rows,
-            max_nums_in_cols,
-            background_mesh,
-            cross_mesh,
-            game_state,
-            game_zone,
-            undo_zone,
-            click_state: None,
-            play_once_zone,
-            play_many_zone,
-            pause_zone,
-            ai_player,
-            transparent_cross_mesh,
-            stopped_mesh,
-            done_mesh,
-            in_progress_mesh
-        }
-        // finally, we got to creating GAME STATE
+#[derive(Subcommand)]
+enum Command {
+    /// Solve the level without opening a window and print the grid
+    Solve {
+        level_path: String,
+        /// Strategies to run, weakest first; all of them by default
+        #[arg(long, value_delimiter = ',')]
+        strategy: Vec<StrategyKind>
+    },
+    /// Make a level from a black-and-white picture or a random grid
+    #[command(group(ArgGroup::new("source").required(true).args(["from_image", "width"])))]
+    Generate {
+        /// Picture to take the cells from
+        #[arg(long)]
+        from_image: Option<String>,
+        /// Pixels darker than this become filled cells
+        #[arg(long, default_value_t = 128)]
+        threshold: u8,
+        /// Width of a random level
+        #[arg(long, requires = "height", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        width: Option<usize>,
+        /// Height of a random level
+        #[arg(long, requires = "width", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        height: Option<usize>,
+        /// Share of filled cells in a random level
+        #[arg(long, default_value_t = 0.5, conflicts_with = "from_image")]
+        density: f64,
+        /// Seed of a random level, picked at random and printed if missing
+        #[arg(long, conflicts_with = "from_image")]
+        seed: Option<u64>,
+        /// Keep only random levels that line solving alone can finish
+        #[arg(long, conflicts_with = "from_image")]
+        line_solvable: bool,
+        /// How many random grids to try before giving up
+        #[arg(long, default_value_t = 1000, conflicts_with = "from_image")]
+        attempts: usize,
+        /// Where to write the level, yaml to stdout by default
+        #[arg(long, short)]
+        output: Option<String>,
+        /// Run the solver and fail if the level has more than one solution
+        #[arg(long)]
+        check_unique: bool
+    },
+    /// Rate the difficulty of levels and list them from the easiest to the hardest
+    Rate {
+        #[arg(required = true)]
+        level_paths: Vec<String>,
+        /// Print every step of the solution with the strategy that made it
+        #[arg(long)]
+        trace: bool
+    },
+    /// Convert a level to another format, picked by the output file extension
+    Convert {
+        level_path: String,
+        output_path: String
     }
+}
 
-    fn board_cell(&self, x: usize, y: usize) -> graphics::Rect {
-        graphics::Rect::new(
-            cell_num_to_coord(self.max_nums_in_rows + x),
-            cell_num_to_coord(self.max_nums_in_cols + y),
-            CELL_SIZE,
-            CELL_SIZE
-        )
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
     }
+}
 
-    fn row_description_cell(&self, x: usize, y: usize) -> graphics::Rect {
-        graphics::Rect::new(
-            cell_num_to_coord(x),
-            cell_num_to_coord(self.max_nums_in_cols + y),
-            CELL_SIZE,
-            CELL_SIZE
-        )
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    match cli.command {
+        Some(Command::Solve { level_path, strategy }) => {
+            let strategies = if strategy.is_empty() { StrategyKind::ALL.to_vec() } else { strategy };
+            std::process::exit(headless::solve(&level_path, &strategies)?);
+        },
+        Some(Command::Generate { from_image, threshold, width, height, density, seed, line_solvable, attempts, output, check_unique }) => {
+            let code = match (from_image, width, height) {
+                (Some(from_image), _, _) => generate::from_image(&from_image, threshold, output.as_deref(), check_unique)?,
+                (None, Some(width), Some(height)) => {
+                    let options = generate::RandomOptions { width, height, density, seed, line_solvable, attempts };
+                    generate::random(&options, output.as_deref())?
+                },
+                _ => unreachable!("clap requires an image or both sizes")
+            };
+            std::process::exit(code);
+        },
+        Some(Command::Rate { level_paths, trace }) => {
+            std::process::exit(headless::rate(&level_paths, trace));
+        },
+        Some(Command::Convert { level_path, output_path }) => {
+            save_level(&load_level(&level_path)?, &output_path)?;
+            return Ok(());
+        },
+        None => {}
     }
 
-    fn col_description_cell(&self, x: usize, y: usize) -> graphics::Rect {
-        graphics::Rect::new(
-            cell_num_to_coord(self.max_nums_in_rows + x),
-            cell_num_to_coord(y),
-            CELL_SIZE,
-            CELL_SIZE
-        )
+    let profile_path = cli.profile.unwrap_or_else(default_profile_path);
+    if let Some(levels_dir) = cli.level_path.as_deref().filter(|x| cli.resume.is_none() && std::path::Path::new(x).is_dir()) {
+        return browse(levels_dir.to_string(), profile_path);
     }
 
-    fn button_cell(&self, x: usize) -> graphics::Rect {
-        graphics::Rect::new(
-            cell_num_to_coord(x),
-            cell_num_to_coord(self.max_nums_in_cols + self.game_state.height()),
-            CELL_SIZE,
-            CELL_SIZE
-        )
-    }
+    let save = cli.resume.as_deref().map(SaveGame::from_file).transpose()?;
+    let level_path = match (cli.level_path, &save) {
+        (Some(level_path), _) => level_path,
+        (None, Some(save)) => save.level_path.clone(),
+        (None, None) => unreachable!("clap requires a level or a save file")
+    };
+    let lvl_desc = load_level(&level_path)?;
+    let game_state = match &save {
+        Some(save) => save.restore(&lvl_desc)?,
+        None => GameState::new(lvl_desc.clone().into())
+    };
+    let save_path = cli.resume.unwrap_or_else(|| default_save_path(&level_path));
+    play(lvl_desc, game_state, level_path, save_path, profile_path)
 }
 
+#[cfg(feature = "gui")]
+fn play(lvl_desc: LevelDescriptionTemplate, game_state: GameState, level_path: String, save_path: String, profile_path: String) -> Result<(), Box<dyn std::error::Error>> {
+    app::run_level(lvl_desc, game_state, level_path, save_path, profile_path)?;
+    Ok(())
+}
 
+#[cfg(feature = "gui")]
+fn browse(levels_dir: String, profile_path: String) -> Result<(), Box<dyn std::error::Error>> {
+    app::run_menu(levels_dir, profile_path)?;
+    Ok(())
+}
 
-impl EventHandler for MyGame {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
-        // Update code here...
-
-        let pos = _ctx.mouse.position();
-
-        {
-            let mut builder = TransactionBuilder::new(self.game_state.grid());
-            self.ai_player.try_perform_turn(self.game_state.lvl_desc(), &mut builder);
-            let transaction = builder.to_transaction(self.game_state.grid());
-            self.game_state.apply_transaction(&transaction);
-        }
-
-        if self.undo_zone.in_clickable_zone(pos) {
-            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
-                self.game_state.undo();
-                self.ai_player.restart_clock();
-            }
-        }
-
-        if self.play_once_zone.in_clickable_zone(pos) {
-            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
-                let mut builder = TransactionBuilder::new(self.game_state.grid());
-
-                self.ai_player.play_single_turn_emergency(self.game_state.lvl_desc(), &mut builder);
-
-                let transaction = builder.to_transaction(self.game_state.grid());
-
-                self.game_state.apply_transaction(&transaction);
-
-                self.ai_player.restart_clock();
-            }
-        }
-
-        if self.play_many_zone.in_clickable_zone(pos) {
-            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
-                self.ai_player.start_play();
-            }
-        }
-
-        if self.pause_zone.in_clickable_zone(pos) {
-            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
-                self.ai_player.pause_play();
-            }
-        }
-
-        if self.game_zone.in_clickable_zone(pos) {
-
-            let x_offset = cell_num_to_coord(self.max_nums_in_rows);
-            let y_offset = cell_num_to_coord(self.max_nums_in_cols);
-
-            let in_game_pos = mint::Point2::<f32>::from([pos.x - x_offset, pos.y - y_offset]);
-
-            let mut col_number = in_game_pos.x.div_euclid(CELL_SIZE) as usize;
-            let mut row_number = in_game_pos.y.div_euclid(CELL_SIZE) as usize;
-
-            if row_number < self.game_state.height() && col_number < self.game_state.width() {
-                if let None = self.click_state {
-                    let new_state = if _ctx.mouse.button_pressed(MouseButton::Left) {
-                        if self.game_state.get(col_number, row_number) == Filled { Some(Empty) } else { Some(Filled) }
-                    } else if _ctx.mouse.button_pressed(MouseButton::Right) {
-                        if self.game_state.get(col_number, row_number) == Crossed { Some(Empty) } else { Some(Crossed) }
-                    } else {
-                        None
-                    };
-
-                    if let Some(state) = new_state {
-                        self.click_state = Some(GameClickState {
-                            is_horizontal: true,
-                            is_vertical: true,
-                            col: col_number,
-                            row: row_number,
-                            state: state
-                        })
-                    }
-                } else if !_ctx.mouse.button_pressed(MouseButton::Left) && !_ctx.mouse.button_pressed(MouseButton::Right) {
-                    self.click_state = None;
-                }
-
-                if let Some(click_state) = &self.click_state {
-                    if click_state.is_horizontal && click_state.is_vertical {
-                        //Diagonal move not allowed
-                        if (click_state.row != row_number && click_state.col != col_number) {
-                            self.click_state = None;
-                        } else if (click_state.row != row_number) {
-                            self.click_state = Some(GameClickState {
-                                is_horizontal: false,
-                                is_vertical: true,
-                                col: click_state.col,
-                                row: click_state.row,
-                                state: click_state.state
-                            });
-                        } else if (click_state.col != col_number) {
-                            self.click_state = Some(GameClickState {
-                                is_horizontal: true,
-                                is_vertical: false,
-                                col: click_state.col,
-                                row: click_state.row,
-                                state: click_state.state
-                            });
-                        }
-                    } else if click_state.is_horizontal {
-                        row_number = click_state.row;
-                    } else if click_state.is_vertical {
-                        col_number = click_state.col;
-                    } else {
-                        panic!("Impossible state");
-                    }
-                }
-
-                if let Some(click_state) = &self.click_state {
-                    if self.game_state.get(col_number, row_number) != click_state.state {
-                        self.game_state.set(col_number, row_number, click_state.state);
-                        self.ai_player.restart_clock();
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        let mut canvas = graphics::Canvas::from_frame(ctx, Color::WHITE);
-
-
-        for (i,row) in self.game_state.lvl_desc().rows.iter().enumerate() {
-            for (j,cell) in row.parts.iter().rev().enumerate() {
-                let dest_point = Vec2::new(
-                    ((self.max_nums_in_rows - j - 1) as f32 + 0.5) * CELL_SIZE, 
-                    ((self.max_nums_in_cols + i) as f32 + 0.5) * CELL_SIZE
-                );
-                let text = graphics::Text::new(format!("{}", cell.elements_count))
-                    .set_font(MAIN_FONT)
-                    .set_layout(TextLayout::center())
-                    .set_scale(CELL_SIZE / 2.0)
-                    .clone();
-                canvas.draw( &text, graphics::DrawParam::from(dest_point).color(Color::BLACK));
-            }
-        }
-
-        for (i,col) in self.game_state.lvl_desc().cols.iter().enumerate() {
-            for (j,cell) in col.parts.iter().rev().enumerate() {
-                let dest_point = Vec2::new(
-                    ((self.max_nums_in_rows + i) as f32 + 0.5) * CELL_SIZE, 
-                    ((self.max_nums_in_cols - j - 1) as f32 + 0.5) * CELL_SIZE,
-                );
-                let text = graphics::Text::new(format!("{}", cell.elements_count))
-                    .set_font(MAIN_FONT)
-                    .set_layout(TextLayout::center())
-                    .set_scale(CELL_SIZE / 2.0)
-                    .clone();
-                canvas.draw( &text, graphics::DrawParam::from(dest_point).color(Color::BLACK));
-            }
-        }
-
-        for (x,y,cell) in self.game_state.grid_to_iter() {
-            use game_state::CellState::*;
-            match cell {
-                Empty => {},
-                Filled => {
-                    canvas.draw(
-                        &graphics::Quad,
-                        graphics::DrawParam::new()
-                            .dest_rect(self.board_cell(x, y))
-                            .color(Color::BLACK)
-                    );
-                },
-                Crossed => {
-                    canvas.draw(
-                        &self.cross_mesh,
-                        graphics::DrawParam::new()
-                            .dest_rect(self.board_cell(x, y))
-                    );
-                },
-            }
-        }
-
-        for (row_num, row_description) in self.game_state.lvl_desc().rows.iter().enumerate() {
-            for (row_part_num, row_description_part) in row_description.parts.iter().enumerate() {
-                if row_description_part.is_completed {
-                    canvas.draw(
-                        &self.transparent_cross_mesh,
-                        graphics::DrawParam::new()
-                            .dest_rect(self.row_description_cell(self.max_nums_in_rows - row_part_num - 1, row_num))
-                    )
-                }
-            }
-        }
-
-        for (col_num, col_description) in self.game_state.lvl_desc().cols.iter().enumerate() {
-            for (col_part_num, col_description_part) in col_description.parts.iter().enumerate() {
-                if col_description_part.is_completed {
-                    canvas.draw(
-                        &self.transparent_cross_mesh,
-                        graphics::DrawParam::new()
-                            .dest_rect(self.col_description_cell(col_num, self.max_nums_in_cols - col_part_num - 1))
-                    )
-                }
-            }
-        }
-
-        self.undo_zone.draw(ctx.mouse.position(), &mut canvas);
-        self.play_once_zone.draw(ctx.mouse.position(), &mut canvas);
-        self.play_many_zone.draw(ctx.mouse.position(), &mut canvas);
-        self.pause_zone.draw(ctx.mouse.position(), &mut canvas);
-
-        let ai_mesh = if (self.ai_player.is_active()) {
-            if self.game_state.lvl_desc().is_done() {
-                &self.done_mesh
-            }
-            else {
-                &self.in_progress_mesh
-            }
-        }
-        else {
-            &self.stopped_mesh
-        };
-
-        canvas.draw(
-            ai_mesh,
-            graphics::DrawParam::new()
-                .dest_rect(self.button_cell(6))
-        );
+#[cfg(not(feature = "gui"))]
+fn play(_lvl_desc: LevelDescriptionTemplate, _game_state: GameState, _level_path: String, _save_path: String, _profile_path: String) -> Result<(), Box<dyn std::error::Error>> {
+    Err("this build has no window, rebuild with the `gui` feature or use the `solve` subcommand".into())
+}
 
-        canvas.draw(&self.background_mesh, graphics::DrawParam::default());
-        canvas.finish(ctx)
-    }
+#[cfg(not(feature = "gui"))]
+fn browse(_levels_dir: String, _profile_path: String) -> Result<(), Box<dyn std::error::Error>> {
+    Err("this build has no window, rebuild with the `gui` feature or use the `rate` subcommand".into())
 }
@@ -0,0 +1,1102 @@
+use std::path;
+use std::sync::mpsc::{self, TryRecvError};
+use std::time::Instant;
+
+use ggez::glam::Vec2;
+use ggez::winit::dpi::LogicalSize;
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Color, TextLayout, Rect, Canvas};
+use ggez::event::{EventHandler, MouseButton};
+use ggez::input::keyboard::{KeyCode, KeyInput, KeyMods};
+use ggez::mint::{Point2, Vector2};
+
+use nonogram_gamer_rs::ai_player::AiPlayer;
+use nonogram_gamer_rs::description::{LevelDescriptionTemplate, PaletteColor};
+use nonogram_gamer_rs::difficulty::{BackgroundRating, Difficulty};
+use nonogram_gamer_rs::game_state::{self, CellState, ColorId, GameState, GameStats};
+use nonogram_gamer_rs::game_state::CellState::{Crossed, Empty};
+use nonogram_gamer_rs::profile::{self, LevelRecord, Profile};
+use nonogram_gamer_rs::save::SaveGame;
+use nonogram_gamer_rs::transaction::TransactionBuilder;
+use nonogram_gamer_rs::strategy::StrategyKind;
+
+use crate::app::Transition;
+use crate::board_view::BoardView;
+use crate::clickable_zone::ClickableZone;
+use crate::meshes;
+
+
+pub const MAIN_FONT: &'static str = "LiberationMono";
+/// Zoom factor of one mouse wheel step
+const ZOOM_STEP: f32 = 1.1;
+/// Buttons and the AI player's status before the counters
+const BUTTON_CELLS: usize = 7;
+/// Room for the time and counters at the end of the button line
+const COUNTER_CELLS: usize = 6;
+/// How long a notice stays over the board
+const NOTICE_SECONDS: f32 = 3.0;
+
+/// Colour of every cell of the picture, `None` for an empty one
+type Solution = Vec<Vec<Option<ColorId>>>;
+
+/// The picture the check button compares the grid with
+enum SolutionState {
+    /// solved on a thread of its own, a level without a stored solution can take long
+    Pending(mpsc::Receiver<Option<Solution>>),
+    Found(Solution),
+    /// the level has no unique solution to check against
+    Missing
+}
+
+impl SolutionState {
+    fn start(level: &LevelDescriptionTemplate) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let level = level.clone();
+        std::thread::spawn(move || {
+            // the window may be closed before the level is solved
+            let _ = sender.send(level.intended_solution());
+        });
+        SolutionState::Pending(receiver)
+    }
+
+    fn poll(&mut self) {
+        if let SolutionState::Pending(receiver) = self {
+            match receiver.try_recv() {
+                Ok(Some(solution)) => *self = SolutionState::Found(solution),
+                Ok(None) | Err(TryRecvError::Disconnected) => *self = SolutionState::Missing,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+    }
+}
+
+struct GameClickState {
+    state: CellState,
+    col: usize,
+    row: usize,
+    is_horizontal: bool,
+    is_vertical: bool
+}
+
+/// Shown over the board once it is solved
+struct Completion {
+    game: GameStats,
+    /// `None` when the profile could not be read or written
+    record: Option<LevelRecord>,
+    new_best: bool,
+    /// the overlay is closed when a click that started on it ends, not by the release of the last stroke
+    pressed: bool
+}
+
+fn to_color(color: &PaletteColor) -> Color {
+    let [r, g, b] = color.to_rgb().unwrap_or([0, 0, 0]);
+    Color::from_rgb(r, g, b)
+}
+
+/// Black or white, whichever reads better on the background
+fn text_color_on(background: Color) -> Color {
+    let luminance = 0.299 * background.r + 0.587 * background.g + 0.114 * background.b;
+    if luminance > 0.6 { Color::BLACK } else { Color::WHITE }
+}
+
+/// The level's title and author, the title is kept secret until the level is solved
+fn window_title(level: &LevelDescriptionTemplate, level_path: &str, solved: bool) -> String {
+    let name = match &level.title {
+        Some(title) if solved => title.clone(),
+        _ => path::Path::new(level_path).file_stem().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default()
+    };
+    match &level.author {
+        Some(author) => format!("{} by {} - Nonogram-gamer", name, author),
+        None => format!("{} - Nonogram-gamer", name)
+    }
+}
+
+pub struct MyGame {
+    max_nums_in_rows: usize,
+    max_nums_in_cols: usize,
+    view: BoardView,
+    cross_mesh: graphics::Mesh,
+    transparent_cross_mesh: graphics::Mesh,
+    game_state: GameState,
+    undo_zone: ClickableZone,
+    redo_zone: ClickableZone,
+    click_state: Option<GameClickState>,
+    /// cell moved by the keyboard, hidden until a movement key is pressed
+    cursor: Option<(usize, usize)>,
+    /// painting with Shift+arrows, `col` and `row` are where it started
+    keyboard_stroke: Option<GameClickState>,
+    cursor_mesh: graphics::Mesh,
+    /// colours of the level, black for a black and white one
+    palette: Vec<Color>,
+    /// colour painted by the left button and Space
+    color: ColorId,
+
+    play_once_zone: ClickableZone,
+    play_many_zone: ClickableZone,
+    pause_zone: ClickableZone,
+    ai_player: AiPlayer,
+
+    check_zone: ClickableZone,
+    solution: SolutionState,
+    mistakes: Vec<(usize, usize)>,
+    /// shown over the board for a few seconds since it was given
+    notice: Option<(String, Instant)>,
+
+    done_mesh: graphics::Mesh,
+    stopped_mesh: graphics::Mesh,
+    in_progress_mesh: graphics::Mesh,
+
+    /// `None` while the level is being rated
+    difficulty: Option<Difficulty>,
+    rating: Option<BackgroundRating>,
+
+    level: LevelDescriptionTemplate,
+    level_path: String,
+    save_path: String,
+    profile_path: String,
+
+    /// opened from the level menu: Escape and closing the completion screen go back to it instead of quitting
+    return_to_menu: bool,
+    /// the game is recorded in the profile once, undoing and solving again does not count,
+    /// and a level that was already solved when opened is not recorded again
+    recorded: bool,
+    /// start of the time not yet added to the game's counters, `None` while the timer is stopped
+    timer_start: Option<Instant>,
+    completion: Option<Completion>,
+    pub transition: Option<Transition>
+}
+
+impl MyGame {
+    pub fn new(ctx: &mut Context, lvl_desc: LevelDescriptionTemplate, game_state: GameState, level_path: String, save_path: String, profile_path: String, return_to_menu: bool) -> MyGame {
+        // Eager evaluation of screen size and margins
+        let max_nums_in_rows = lvl_desc.rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let max_nums_in_cols = lvl_desc.cols.iter().map(|r| r.len()).max().unwrap_or(0);
+        let palette_cells = if lvl_desc.is_colored() { lvl_desc.colors().len() } else { 0 };
+        let window = BoardView::preferred_window_size(game_state.width(), game_state.height(), max_nums_in_rows, max_nums_in_cols, palette_cells);
+        ctx.gfx.window().set_inner_size(LogicalSize::new(window.x, window.y));
+        let mut view = BoardView::new(game_state.width(), game_state.height(), max_nums_in_rows, max_nums_in_cols, BUTTON_CELLS + COUNTER_CELLS, window);
+        view.set_palette_cells(palette_cells);
+        // prepare cross texture
+        let cross_mesh = meshes::cross(0.05, Color::from_rgb(100, 100, 100), &ctx);
+        let transparent_cross_mesh = meshes::cross(0.05, Color::from_rgba(100, 100, 100, 100), &ctx);
+        let recorded = game_state::is_solved(game_state.grid(), game_state.lvl_desc());
+        ctx.gfx.set_window_title(&window_title(&lvl_desc, &level_path, recorded));
+
+        let default_button_color = Color::from_rgb(0, 0, 0);
+        let default_button_hover_color = Color::from_rgb(127, 127, 127);
+        let width = 0.02;
+        // buttons are placed by `layout_buttons`
+        let new_zone = || ClickableZone::new(Point2::<f32>::from([0.0, 0.0]), Vector2::<f32>::from([0.0, 0.0]));
+
+        let mut undo_zone = new_zone();
+        undo_zone.set_mesh_for_draw(meshes::left_arrow(width, default_button_color, &ctx));
+        undo_zone.set_mesh_for_draw_at_hover(meshes::left_arrow(width, default_button_hover_color, &ctx));
+
+        let mut redo_zone = new_zone();
+        redo_zone.set_mesh_for_draw(meshes::right_arrow(width, default_button_color, ctx));
+        redo_zone.set_mesh_for_draw_at_hover(meshes::right_arrow(width, default_button_hover_color, ctx));
+
+        let mut play_once_zone = new_zone();
+        play_once_zone.set_mesh_for_draw(meshes::play_once(width, default_button_color, &ctx));
+        play_once_zone.set_mesh_for_draw_at_hover(meshes::play_once(width, default_button_hover_color, &ctx));
+
+        let mut play_many_zone = new_zone();
+        play_many_zone.set_mesh_for_draw(meshes::play_many(width, default_button_color, &ctx));
+        play_many_zone.set_mesh_for_draw_at_hover(meshes::play_many(width, default_button_hover_color, &ctx));
+
+        let mut pause_zone = new_zone();
+        pause_zone.set_mesh_for_draw(meshes::pause(width, default_button_color, &ctx));
+        pause_zone.set_mesh_for_draw_at_hover(meshes::pause(width, default_button_hover_color, &ctx));
+
+        let mut check_zone = new_zone();
+        check_zone.set_mesh_for_draw(meshes::check(width, default_button_color, ctx));
+        check_zone.set_mesh_for_draw_at_hover(meshes::check(width, default_button_hover_color, ctx));
+
+        let ai_player = AiPlayer::with_strategies(&StrategyKind::ALL);
+
+        let done_mesh = meshes::done(0.02, Color::from_rgb(0, 200, 83), &ctx);
+        let stopped_mesh = meshes::stopped(0.02, Color::from_rgb(255, 23, 68), &ctx);
+        let in_progress_mesh = meshes::in_progress(0.02, Color::from_rgb(254, 223, 88), &ctx);
+
+        let mut my_game = MyGame {
+            max_nums_in_rows,
+            max_nums_in_cols,
+            view,
+            cross_mesh,
+            game_state,
+            undo_zone,
+            redo_zone,
+            click_state: None,
+            cursor: None,
+            keyboard_stroke: None,
+            cursor_mesh: meshes::frame(0.04, Color::from_rgb(41, 121, 255), ctx),
+            palette: lvl_desc.colors().iter().map(to_color).collect(),
+            color: 0,
+            play_once_zone,
+            play_many_zone,
+            pause_zone,
+            ai_player,
+            check_zone,
+            solution: SolutionState::start(&lvl_desc),
+            mistakes: Vec::new(),
+            notice: None,
+            transparent_cross_mesh,
+            stopped_mesh,
+            done_mesh,
+            in_progress_mesh,
+            difficulty: None,
+            rating: None,
+            level: lvl_desc,
+            level_path,
+            save_path,
+            profile_path,
+            return_to_menu,
+            recorded,
+            timer_start: None,
+            completion: None,
+            transition: None
+        };
+        my_game.layout_buttons();
+        my_game
+    }
+
+    /// Moves the buttons to the line at the bottom of the window, needed after every resize
+    fn layout_buttons(&mut self) {
+        let zones = [
+            &mut self.undo_zone,
+            &mut self.redo_zone,
+            &mut self.play_once_zone,
+            &mut self.play_many_zone,
+            &mut self.pause_zone,
+            &mut self.check_zone
+        ];
+        for (i, zone) in zones.into_iter().enumerate() {
+            let rect = self.view.button_cell(i);
+            zone.position = Point2::<f32>::from([rect.x, rect.y]);
+            zone.size = Vector2::<f32>::from([rect.w, rect.h]);
+        }
+    }
+
+    /// Swatches of the palette are on the lines under the buttons, a black and white level has none
+    fn palette_cells(&self) -> usize {
+        if self.level.is_colored() { self.palette.len() } else { 0 }
+    }
+
+    fn palette_cell(&self, color: usize) -> graphics::Rect {
+        self.view.palette_cell(color)
+    }
+
+    fn select_color(&mut self, color: usize) {
+        if color < self.palette_cells() {
+            self.color = color as ColorId;
+        }
+    }
+
+    /// What painting a cell with the selected colour sets it to
+    fn paint_state(&self) -> CellState {
+        CellState::filled(self.color)
+    }
+
+    fn cell_color(&self, color: ColorId) -> Color {
+        self.palette.get(color as usize).copied().unwrap_or(Color::BLACK)
+    }
+
+    fn board_cell(&self, x: usize, y: usize) -> graphics::Rect {
+        self.view.board_cell(x, y)
+    }
+
+    fn row_description_cell(&self, x: usize, y: usize) -> graphics::Rect {
+        self.view.row_clue_cell(x, y)
+    }
+
+    fn col_description_cell(&self, x: usize, y: usize) -> graphics::Rect {
+        self.view.col_clue_cell(x, y)
+    }
+
+    fn undo(&mut self) {
+        self.game_state.undo();
+        self.ai_player.restart_clock();
+    }
+
+    fn redo(&mut self) {
+        self.game_state.redo();
+        self.ai_player.restart_clock();
+    }
+
+    fn play_once(&mut self) {
+        let mut builder = TransactionBuilder::new(self.game_state.grid());
+
+        self.ai_player.play_single_turn_emergency(self.game_state.lvl_desc(), &mut builder);
+
+        let transaction = builder.to_transaction(self.game_state.grid());
+
+        if !transaction.changes.is_empty() {
+            self.game_state.stats_mut().ai_helped = true;
+        }
+        self.game_state.apply_transaction(&transaction);
+
+        self.ai_player.restart_clock();
+    }
+
+    fn set_cursor_cell(&mut self, state: CellState) {
+        if let Some((col, row)) = self.cursor {
+            if self.game_state.get(col, row) != state {
+                self.game_state.set(col, row, state);
+                self.ai_player.restart_clock();
+            }
+        }
+    }
+
+    /// Moves the cursor by one cell, painting the cells it enters when `paint` is set
+    fn move_cursor(&mut self, d_col: isize, d_row: isize, paint: bool) {
+        let (col, row) = match self.cursor {
+            Some(cursor) => cursor,
+            None => {
+                self.cursor = Some((0, 0));
+                return;
+            }
+        };
+
+        if paint && self.keyboard_stroke.is_none() {
+            self.game_state.begin_action();
+            self.keyboard_stroke = Some(GameClickState {
+                is_horizontal: d_row == 0,
+                is_vertical: d_col == 0,
+                col,
+                row,
+                state: self.game_state.get(col, row)
+            });
+        }
+        if let Some(stroke) = &self.keyboard_stroke {
+            // same axis lock as a mouse stroke
+            if (stroke.is_horizontal && d_row != 0) || (stroke.is_vertical && d_col != 0) {
+                return;
+            }
+        }
+
+        let new_col = col.saturating_add_signed(d_col).min(self.game_state.width() - 1);
+        let new_row = row.saturating_add_signed(d_row).min(self.game_state.height() - 1);
+        self.cursor = Some((new_col, new_row));
+        self.view.scroll_to(new_col, new_row);
+
+        if let Some(state) = self.keyboard_stroke.as_ref().map(|x| x.state) {
+            self.set_cursor_cell(state);
+        }
+    }
+
+    /// Cell of the stroke being painted and the length of the block it is part of,
+    /// along the axis the stroke is locked to
+    fn stroke_block(&self, mouse_position: Point2<f32>) -> Option<((usize, usize), usize)> {
+        let (stroke, (col, row)) = match (&self.click_state, &self.keyboard_stroke) {
+            (Some(stroke), _) => (stroke, self.view.cell_at(mouse_position)?),
+            (None, Some(stroke)) => (stroke, self.cursor?),
+            (None, None) => return None
+        };
+        if stroke.state == Empty {
+            return None;
+        }
+        let (col, row) = match (stroke.is_horizontal, stroke.is_vertical) {
+            (true, false) => (col, stroke.row),
+            (false, true) => (stroke.col, row),
+            _ => (col, row)
+        };
+        if self.game_state.get(col, row) != stroke.state {
+            return None;
+        }
+        let length = game_state::block_length(self.game_state.grid(), col, row, stroke.is_horizontal);
+        Some(((col, row), length))
+    }
+
+    fn zoom_at_center(&mut self, factor: f32) {
+        let viewport = self.view.viewport();
+        self.view.zoom_at(Point2::<f32>::from([viewport.x + viewport.w / 2.0, viewport.y + viewport.h / 2.0]), factor);
+    }
+
+    fn end_keyboard_stroke(&mut self) {
+        if self.keyboard_stroke.take().is_some() {
+            self.game_state.end_action();
+        }
+    }
+
+    /// Difficulty known from the level menu, the level is not rated again
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = Some(difficulty);
+        self.rating = None;
+    }
+
+    /// Starts rating the level on the first call, hard levels take long to analyze
+    fn update_difficulty(&mut self) {
+        if self.difficulty.is_some() {
+            return;
+        }
+        let rating = self.rating.get_or_insert_with(|| BackgroundRating::start(vec![(self.level_path.clone(), self.level.clone())]));
+        if let Some((_, difficulty)) = rating.finished().pop() {
+            self.difficulty = Some(difficulty);
+            self.rating = None;
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.game_state.end_action();
+        let running = self.timer_start.is_some();
+        self.stop_timer();
+        match SaveGame::new(&self.level_path, &self.level, &self.game_state).to_file(&self.save_path) {
+            Ok(()) => eprintln!("progress saved to {}", self.save_path),
+            Err(e) => eprintln!("error: {}", e)
+        }
+        if running {
+            self.timer_start = Some(Instant::now());
+        }
+    }
+
+    fn stop_timer(&mut self) {
+        if let Some(start) = self.timer_start.take() {
+            self.game_state.stats_mut().seconds += start.elapsed().as_secs_f64();
+        }
+    }
+
+    /// The timer runs from the first move until the board is solved, the solved game is recorded in the profile
+    fn check_solved(&mut self, ctx: &Context) {
+        let solved = game_state::is_solved(self.game_state.grid(), self.game_state.lvl_desc());
+        let running = !solved && self.game_state.stats().moves > 0;
+        if running && self.timer_start.is_none() {
+            self.timer_start = Some(Instant::now());
+        }
+        else if !running {
+            self.stop_timer();
+        }
+
+        if solved && !self.recorded {
+            self.recorded = true;
+            self.finish(ctx);
+        }
+    }
+
+    fn finish(&mut self, ctx: &Context) {
+        ctx.gfx.set_window_title(&window_title(&self.level, &self.level_path, true));
+        self.game_state.end_action();
+        let game = *self.game_state.stats();
+        let result = Profile::from_file(&self.profile_path).and_then(|mut profile| {
+            let new_best = profile.add_solved(&self.level, &game);
+            profile.to_file(&self.profile_path)?;
+            Ok((profile.record(&self.level).copied(), new_best))
+        });
+        let (record, new_best) = result.unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            (None, false)
+        });
+        self.completion = Some(Completion { game, record, new_best, pressed: false });
+    }
+
+    fn close_completion(&mut self) {
+        if self.return_to_menu {
+            self.transition = Some(Transition::OpenMenu);
+        }
+        else {
+            self.completion = None;
+        }
+    }
+
+    /// Seconds played, with the part of the timer not yet added to the game's counters
+    fn elapsed(&self) -> f64 {
+        self.game_state.stats().seconds + self.timer_start.map_or(0.0, |x| x.elapsed().as_secs_f64())
+    }
+
+    /// Covers the window with the finished picture, without crosses, under the level's title
+    fn draw_completion(&self, canvas: &mut Canvas, completion: &Completion) {
+        let window = self.view.window();
+        let grey = Color::from_rgb(127, 127, 127);
+        canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(Rect::new(0.0, 0.0, window.x, window.y)).color(Color::WHITE));
+
+        let game = &completion.game;
+        let mut lines = Vec::new();
+        let credits = [
+            self.level.author.as_ref().map(|x| format!("by {}", x)),
+            self.level.copyright.clone(),
+            self.level.license.as_ref().map(|x| format!("license: {}", x)),
+            self.level.description.clone(),
+            Some(self.level.tags.iter().map(|x| format!("#{}", x)).collect::<Vec<_>>().join(" ")).filter(|x| !x.is_empty())
+        ];
+        for line in credits.into_iter().flatten() {
+            lines.extend(line.lines().map(|x| (x.to_string(), grey)));
+        }
+        lines.extend([
+            (format!("solved in {}", profile::format_time(game.seconds)), Color::from_rgb(0, 200, 83)),
+            (format!("moves {}  undos {}", game.moves, game.undos), Color::BLACK)
+        ]);
+        if game.ai_helped {
+            lines.push(("with help from the AI".to_string(), grey));
+        }
+        match (&completion.record, completion.new_best) {
+            (Some(_), true) => lines.push(("new best".to_string(), Color::from_rgb(41, 121, 255))),
+            (Some(record), false) => lines.push((format!("best {}", profile::format_time(record.best.seconds)), Color::BLACK)),
+            (None, _) => {}
+        }
+        if let Some(record) = &completion.record {
+            lines.push((format!("solved {} times", record.times_solved), grey));
+        }
+        let hint = if self.return_to_menu { "Enter to go back to the levels" } else { "Enter to continue" };
+        lines.push((hint.to_string(), grey));
+
+        let scale = (window.x / 24.0).clamp(12.0, 32.0);
+        let line_height = scale * 1.5;
+        let title_height = line_height * 1.5;
+        let title = self.level.title.as_deref().unwrap_or("Solved!");
+        let text = graphics::Text::new(title)
+            .set_font(MAIN_FONT)
+            .set_layout(TextLayout::center())
+            .set_scale(scale * 1.5)
+            .clone();
+        canvas.draw(&text, graphics::DrawParam::from(Vec2::new(window.x / 2.0, title_height / 2.0)).color(Color::BLACK));
+
+        // the picture takes the space left between the title and the lines
+        let margin = scale;
+        let lines_top = window.y - lines.len() as f32 * line_height - margin / 2.0;
+        let area = Rect::new(margin, title_height, window.x - 2.0 * margin, lines_top - title_height - margin / 2.0);
+        let (width, height) = (self.game_state.width(), self.game_state.height());
+        let cell_size = (area.w / width as f32).min(area.h / height as f32).max(0.0);
+        let picture = Rect::new(
+            area.x + (area.w - cell_size * width as f32) / 2.0,
+            area.y + (area.h - cell_size * height as f32) / 2.0,
+            cell_size * width as f32,
+            cell_size * height as f32
+        );
+        for (x, y, cell) in self.game_state.grid_to_iter() {
+            if let Some(color) = cell.color() {
+                let rect = Rect::new(picture.x + x as f32 * cell_size, picture.y + y as f32 * cell_size, cell_size, cell_size);
+                canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(rect).color(self.cell_color(color)));
+            }
+        }
+
+        for (i, (line, color)) in lines.iter().enumerate() {
+            let text = graphics::Text::new(line.as_str())
+                .set_font(MAIN_FONT)
+                .set_layout(TextLayout::center())
+                .set_scale(scale)
+                .clone();
+            let dest_point = Vec2::new(window.x / 2.0, lines_top + (i as f32 + 0.5) * line_height);
+            canvas.draw(&text, graphics::DrawParam::from(dest_point).color(*color));
+        }
+    }
+
+    /// Clues of coloured levels are written on their colour, returns the colour of the text
+    fn draw_clue_background(&self, canvas: &mut Canvas, rect: Rect, color: ColorId) -> Color {
+        if !self.level.is_colored() {
+            return Color::BLACK;
+        }
+        let background = self.cell_color(color);
+        let inset = rect.w * 0.08;
+        canvas.draw(
+            &graphics::Quad,
+            graphics::DrawParam::new()
+                .dest_rect(Rect::new(rect.x + inset, rect.y + inset, rect.w - 2.0 * inset, rect.h - 2.0 * inset))
+                .color(background)
+        );
+        text_color_on(background)
+    }
+
+    fn button_cell(&self, x: usize) -> graphics::Rect {
+        self.view.button_cell(x)
+    }
+
+    /// Grid lines through the visible part of the board and its headers
+    fn background_mesh(&self, ctx: &Context) -> GameResult<graphics::Mesh> {
+        let cell_size = self.view.cell_size();
+        let viewport = self.view.viewport();
+        let board = self.view.board();
+        let line_color = Color::from_rgb(50, 99, 168);
+
+        let mb = &mut graphics::MeshBuilder::new();
+        for i in 1..self.game_state.height() {
+            let h = board.y + i as f32 * cell_size;
+            if h > viewport.y && h < viewport.bottom() {
+                mb.line(&[Vec2::new(0.0, h), Vec2::new(board.right().min(viewport.right()), h)], 2.0, line_color)?;
+            }
+        }
+        for i in 1..self.game_state.width() {
+            let w = board.x + i as f32 * cell_size;
+            if w > viewport.x && w < viewport.right() {
+                mb.line(&[Vec2::new(w, 0.0), Vec2::new(w, board.bottom().min(viewport.bottom()))], 2.0, line_color)?;
+            }
+        }
+        let left = board.x.max(viewport.x);
+        let top = board.y.max(viewport.y);
+        mb.rectangle(
+            graphics::DrawMode::stroke(4.0),
+            graphics::Rect::new(left, top, board.right().min(viewport.right()) - left, board.bottom().min(viewport.bottom()) - top),
+            graphics::Color::BLACK
+        )?;
+        Ok(graphics::Mesh::from_data(ctx, mb.build()))
+    }
+}
+
+
+
+/// 1 to 9 on the main keys or the keypad
+fn digit(key: KeyCode) -> Option<usize> {
+    let digits = [
+        (KeyCode::Key1, KeyCode::Numpad1), (KeyCode::Key2, KeyCode::Numpad2), (KeyCode::Key3, KeyCode::Numpad3),
+        (KeyCode::Key4, KeyCode::Numpad4), (KeyCode::Key5, KeyCode::Numpad5), (KeyCode::Key6, KeyCode::Numpad6),
+        (KeyCode::Key7, KeyCode::Numpad7), (KeyCode::Key8, KeyCode::Numpad8), (KeyCode::Key9, KeyCode::Numpad9)
+    ];
+    digits.iter().position(|(main, keypad)| key == *main || key == *keypad).map(|x| x + 1)
+}
+
+impl EventHandler for MyGame {
+    fn update(&mut self, _ctx: &mut Context) -> GameResult {
+        // Update code here...
+        self.update_difficulty();
+        self.solution.poll();
+
+        let pos = _ctx.mouse.position();
+
+        // the AI waits for the stroke being painted to end, its move is an undo step of its own
+        if !self.game_state.in_action() {
+            let mut builder = TransactionBuilder::new(self.game_state.grid());
+            self.ai_player.try_perform_turn(self.game_state.lvl_desc(), &mut builder);
+            let transaction = builder.to_transaction(self.game_state.grid());
+            if !transaction.changes.is_empty() {
+                self.game_state.stats_mut().ai_helped = true;
+            }
+            self.game_state.apply_transaction(&transaction);
+        }
+
+        let mouse_pressed = _ctx.mouse.button_pressed(MouseButton::Left) || _ctx.mouse.button_pressed(MouseButton::Right);
+        if !mouse_pressed && self.keyboard_stroke.is_none() {
+            self.click_state = None;
+            self.game_state.end_action();
+        }
+
+        if let Some(completion) = self.completion.as_mut() {
+            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
+                completion.pressed = true;
+            }
+            else if completion.pressed && _ctx.mouse.button_just_released(MouseButton::Left) {
+                self.close_completion();
+            }
+            return Ok(());
+        }
+
+        if self.undo_zone.in_clickable_zone(pos) {
+            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
+                self.undo();
+            }
+        }
+
+        if self.redo_zone.in_clickable_zone(pos) && _ctx.mouse.button_just_pressed(MouseButton::Left) {
+            self.redo();
+        }
+
+        if self.play_once_zone.in_clickable_zone(pos) {
+            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
+                self.play_once();
+            }
+        }
+
+        if self.play_many_zone.in_clickable_zone(pos) {
+            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
+                self.ai_player.start_play();
+            }
+        }
+
+        if self.pause_zone.in_clickable_zone(pos) {
+            if _ctx.mouse.button_just_pressed(MouseButton::Left) {
+                self.ai_player.pause_play();
+            }
+        }
+
+        if self.check_zone.in_clickable_zone(pos) && _ctx.mouse.button_just_pressed(MouseButton::Left) {
+            match &self.solution {
+                SolutionState::Found(solution) => self.mistakes = game_state::find_mistakes(self.game_state.grid(), solution),
+                SolutionState::Pending(_) => self.notice = Some(("still solving the level, check again in a moment".to_string(), Instant::now())),
+                SolutionState::Missing => self.notice = Some(("the level has no unique solution to check against".to_string(), Instant::now()))
+            }
+        }
+
+        if _ctx.mouse.button_just_pressed(MouseButton::Left) {
+            if let Some(color) = (0..self.palette_cells()).find(|x| self.palette_cell(*x).contains(pos)) {
+                self.select_color(color);
+            }
+        }
+
+        if let Some((mut col_number, mut row_number)) = self.view.cell_at(pos) {
+            if let None = self.click_state {
+                let new_state = if _ctx.mouse.button_pressed(MouseButton::Left) {
+                    let paint = self.paint_state();
+                    if self.game_state.get(col_number, row_number) == paint { Some(Empty) } else { Some(paint) }
+                } else if _ctx.mouse.button_pressed(MouseButton::Right) {
+                    if self.game_state.get(col_number, row_number) == Crossed { Some(Empty) } else { Some(Crossed) }
+                } else {
+                    None
+                };
+
+                if let Some(state) = new_state {
+                    // everything painted until the buttons are released is undone at once
+                    self.game_state.begin_action();
+                    self.click_state = Some(GameClickState {
+                        is_horizontal: true,
+                        is_vertical: true,
+                        col: col_number,
+                        row: row_number,
+                        state: state
+                    })
+                }
+            } else if !_ctx.mouse.button_pressed(MouseButton::Left) && !_ctx.mouse.button_pressed(MouseButton::Right) {
+                self.click_state = None;
+            }
+
+            if let Some(click_state) = &self.click_state {
+                if click_state.is_horizontal && click_state.is_vertical {
+                    //Diagonal move not allowed
+                    if click_state.row != row_number && click_state.col != col_number {
+                        self.click_state = None;
+                    } else if click_state.row != row_number {
+                        self.click_state = Some(GameClickState {
+                            is_horizontal: false,
+                            is_vertical: true,
+                            col: click_state.col,
+                            row: click_state.row,
+                            state: click_state.state
+                        });
+                    } else if click_state.col != col_number {
+                        self.click_state = Some(GameClickState {
+                            is_horizontal: true,
+                            is_vertical: false,
+                            col: click_state.col,
+                            row: click_state.row,
+                            state: click_state.state
+                        });
+                    }
+                } else if click_state.is_horizontal {
+                    row_number = click_state.row;
+                } else if click_state.is_vertical {
+                    col_number = click_state.col;
+                } else {
+                    panic!("Impossible state");
+                }
+            }
+
+            if let Some(click_state) = &self.click_state {
+                if self.game_state.get(col_number, row_number) != click_state.state {
+                    self.game_state.set(col_number, row_number, click_state.state);
+                    self.ai_player.restart_clock();
+                }
+            }
+        }
+
+        self.check_solved(_ctx);
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeated: bool) -> GameResult {
+        if self.completion.is_some() {
+            if matches!(input.keycode, Some(KeyCode::Return) | Some(KeyCode::NumpadEnter) | Some(KeyCode::Space) | Some(KeyCode::Escape)) {
+                self.close_completion();
+            }
+            return Ok(());
+        }
+
+        let shift = input.mods.contains(KeyMods::SHIFT);
+        if input.mods.contains(KeyMods::CTRL) {
+            match input.keycode {
+                Some(KeyCode::Z) if shift => self.redo(),
+                Some(KeyCode::Z) => self.undo(),
+                Some(KeyCode::Y) | Some(KeyCode::R) => self.redo(),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if let Some(number) = input.keycode.and_then(digit) {
+            self.select_color(number - 1);
+            return Ok(());
+        }
+
+        match input.keycode {
+            Some(KeyCode::Left) | Some(KeyCode::H) => self.move_cursor(-1, 0, shift),
+            Some(KeyCode::Right) | Some(KeyCode::L) => self.move_cursor(1, 0, shift),
+            Some(KeyCode::Up) | Some(KeyCode::K) => self.move_cursor(0, -1, shift),
+            Some(KeyCode::Down) | Some(KeyCode::J) => self.move_cursor(0, 1, shift),
+            Some(KeyCode::Space) | Some(KeyCode::F) => {
+                let paint = self.paint_state();
+                let painted = self.cursor.is_some_and(|(col, row)| self.game_state.get(col, row) == paint);
+                self.set_cursor_cell(if painted { Empty } else { paint });
+            },
+            Some(KeyCode::X) => {
+                let crossed = self.cursor.is_some_and(|(col, row)| self.game_state.get(col, row) == Crossed);
+                self.set_cursor_cell(if crossed { Empty } else { Crossed });
+            },
+            Some(KeyCode::Back) | Some(KeyCode::Delete) => self.set_cursor_cell(Empty),
+            Some(KeyCode::C) => self.select_color((self.color as usize + 1) % self.palette_cells().max(1)),
+            Some(KeyCode::U) => self.undo(),
+            Some(KeyCode::N) => self.play_once(),
+            Some(KeyCode::P) => self.ai_player.start_play(),
+            Some(KeyCode::S) => self.ai_player.pause_play(),
+            Some(KeyCode::Equals) | Some(KeyCode::Plus) | Some(KeyCode::NumpadAdd) => self.zoom_at_center(ZOOM_STEP),
+            Some(KeyCode::Minus) | Some(KeyCode::NumpadSubtract) => self.zoom_at_center(1.0 / ZOOM_STEP),
+            Some(KeyCode::Escape) if self.return_to_menu => self.transition = Some(Transition::OpenMenu),
+            Some(KeyCode::Escape) => ctx.request_quit(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, input: KeyInput) -> GameResult {
+        if matches!(input.keycode, Some(KeyCode::LShift) | Some(KeyCode::RShift)) {
+            self.end_keyboard_stroke();
+        }
+        Ok(())
+    }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> GameResult<bool> {
+        self.save();
+        Ok(false)
+    }
+
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        self.view.resize(Vec2::new(width, height));
+        self.layout_buttons();
+        Ok(())
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+        self.view.zoom_at(ctx.mouse.position(), ZOOM_STEP.powf(y));
+        Ok(())
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, _x: f32, _y: f32, dx: f32, dy: f32) -> GameResult {
+        if ctx.mouse.button_pressed(MouseButton::Middle) {
+            self.view.pan_by(Vec2::new(dx, dy));
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut canvas = graphics::Canvas::from_frame(ctx, Color::WHITE);
+        let cell_size = self.view.cell_size();
+        let window = self.view.window();
+        let headers = self.view.headers();
+        let viewport = self.view.viewport();
+        let board = self.view.board();
+        let mouse_position = ctx.mouse.position();
+        let highlight_color = Color::from_rgba(41, 121, 255, 40);
+        let hovered = self.view.cell_at(mouse_position).or(self.cursor).map(|(col, row)| self.board_cell(col, row));
+
+        if let Some(cell) = hovered {
+            for rect in [Rect::new(board.x, cell.y, board.w, cell.h), Rect::new(cell.x, board.y, cell.w, board.h)] {
+                canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(rect).color(highlight_color));
+            }
+        }
+
+        for (x,y,cell) in self.game_state.grid_to_iter() {
+            use game_state::CellState::*;
+            match cell {
+                Empty => {},
+                Filled | Colored(_) => {
+                    canvas.draw(
+                        &graphics::Quad,
+                        graphics::DrawParam::new()
+                            .dest_rect(self.board_cell(x, y))
+                            .color(self.cell_color(cell.color().unwrap_or(0)))
+                    );
+                },
+                Crossed => {
+                    canvas.draw(
+                        &self.cross_mesh,
+                        graphics::DrawParam::new()
+                            .dest_rect(self.board_cell(x, y))
+                    );
+                },
+            }
+        }
+
+        // a mistake stays highlighted until the cell is changed
+        for (x, y) in self.mistakes.iter().copied() {
+            if self.game_state.get(x, y).is_filled() {
+                canvas.draw(
+                    &graphics::Quad,
+                    graphics::DrawParam::new()
+                        .dest_rect(self.board_cell(x, y))
+                        .color(Color::from_rgb(255, 23, 68))
+                );
+            }
+        }
+
+        if let Some((col, row)) = self.cursor {
+            canvas.draw(
+                &self.cursor_mesh,
+                graphics::DrawParam::new()
+                    .dest_rect(self.board_cell(col, row))
+            );
+        }
+
+        // the headers are pinned, cover the part of the board scrolled under them
+        for rect in [Rect::new(0.0, 0.0, headers.x, window.y), Rect::new(0.0, 0.0, window.x, headers.y)] {
+            canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(rect).color(Color::WHITE));
+        }
+        if let Some(cell) = hovered {
+            for rect in [Rect::new(0.0, cell.y, headers.x, cell.h), Rect::new(cell.x, 0.0, cell.w, headers.y)] {
+                canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(rect).color(highlight_color));
+            }
+        }
+
+        for (i,row) in self.game_state.lvl_desc().rows.iter().enumerate() {
+            for (j,cell) in row.parts.iter().rev().enumerate() {
+                let rect = self.row_description_cell(self.max_nums_in_rows - j - 1, i);
+                let text_color = self.draw_clue_background(&mut canvas, rect, cell.color);
+                let dest_point = Vec2::new(rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+                let text = graphics::Text::new(format!("{}", cell.elements_count))
+                    .set_font(MAIN_FONT)
+                    .set_layout(TextLayout::center())
+                    .set_scale(cell_size / 2.0)
+                    .clone();
+                canvas.draw( &text, graphics::DrawParam::from(dest_point).color(text_color));
+            }
+        }
+
+        for (i,col) in self.game_state.lvl_desc().cols.iter().enumerate() {
+            for (j,cell) in col.parts.iter().rev().enumerate() {
+                let rect = self.col_description_cell(i, self.max_nums_in_cols - j - 1);
+                let text_color = self.draw_clue_background(&mut canvas, rect, cell.color);
+                let dest_point = Vec2::new(rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+                let text = graphics::Text::new(format!("{}", cell.elements_count))
+                    .set_font(MAIN_FONT)
+                    .set_layout(TextLayout::center())
+                    .set_scale(cell_size / 2.0)
+                    .clone();
+                canvas.draw( &text, graphics::DrawParam::from(dest_point).color(text_color));
+            }
+        }
+
+        for (row_num, row_description) in self.game_state.lvl_desc().rows.iter().enumerate() {
+            for (row_part_num, row_description_part) in row_description.parts.iter().enumerate() {
+                if row_description_part.is_completed {
+                    canvas.draw(
+                        &self.transparent_cross_mesh,
+                        graphics::DrawParam::new()
+                            .dest_rect(self.row_description_cell(self.max_nums_in_rows - row_part_num - 1, row_num))
+                    )
+                }
+            }
+        }
+
+        for (col_num, col_description) in self.game_state.lvl_desc().cols.iter().enumerate() {
+            for (col_part_num, col_description_part) in col_description.parts.iter().enumerate() {
+                if col_description_part.is_completed {
+                    canvas.draw(
+                        &self.transparent_cross_mesh,
+                        graphics::DrawParam::new()
+                            .dest_rect(self.col_description_cell(col_num, self.max_nums_in_cols - col_part_num - 1))
+                    )
+                }
+            }
+        }
+
+        // the corner above row clues and left of column clues, clues scrolled under it are hidden
+        let corner = self.view.corner();
+        canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(corner).color(Color::WHITE));
+        if self.max_nums_in_rows > 0 && self.max_nums_in_cols > 0 {
+            let dest_point = Vec2::new(corner.w / 2.0, corner.h / 2.0);
+            let text = graphics::Text::new(self.difficulty.map_or("rating...", |x| x.name()))
+                .set_font(MAIN_FONT)
+                .set_layout(TextLayout::center())
+                .set_scale(cell_size / 4.0)
+                .clone();
+            canvas.draw( &text, graphics::DrawParam::from(dest_point).color(Color::from_rgb(127, 127, 127)));
+        }
+
+        let buttons_line = Rect::new(0.0, viewport.bottom(), window.x, window.y - viewport.bottom());
+        canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(buttons_line).color(Color::WHITE));
+
+        self.undo_zone.draw(ctx.mouse.position(), &mut canvas);
+        self.redo_zone.draw(ctx.mouse.position(), &mut canvas);
+        self.play_once_zone.draw(ctx.mouse.position(), &mut canvas);
+        self.play_many_zone.draw(ctx.mouse.position(), &mut canvas);
+        self.pause_zone.draw(ctx.mouse.position(), &mut canvas);
+        self.check_zone.draw(ctx.mouse.position(), &mut canvas);
+
+        let ai_mesh = if self.ai_player.is_active() {
+            if game_state::is_solved(self.game_state.grid(), self.game_state.lvl_desc()) {
+                &self.done_mesh
+            }
+            else {
+                &self.in_progress_mesh
+            }
+        }
+        else {
+            &self.stopped_mesh
+        };
+
+        canvas.draw(
+            ai_mesh,
+            graphics::DrawParam::new()
+                .dest_rect(self.button_cell(6))
+        );
+
+        // the selected colour is framed, the grey border shows light colours on the white line
+        for (i, color) in self.palette.iter().take(self.palette_cells()).enumerate() {
+            let cell = self.palette_cell(i);
+            let border = Rect::new(cell.x + cell.w * 0.18, cell.y + cell.h * 0.18, cell.w * 0.64, cell.h * 0.64);
+            let swatch = Rect::new(cell.x + cell.w * 0.2, cell.y + cell.h * 0.2, cell.w * 0.6, cell.h * 0.6);
+            canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(border).color(Color::from_rgb(127, 127, 127)));
+            canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(swatch).color(*color));
+            if i == self.color as usize {
+                canvas.draw(&self.cursor_mesh, graphics::DrawParam::new().dest_rect(cell));
+            }
+        }
+
+        // time and counters after the last button
+        let counters = format!("{}  moves {}  undos {}", profile::format_time(self.elapsed()), self.game_state.stats().moves, self.game_state.stats().undos);
+        let counters_cell = self.button_cell(BUTTON_CELLS);
+        let text = graphics::Text::new(counters)
+            .set_font(MAIN_FONT)
+            .set_layout(TextLayout::center())
+            .set_scale(counters_cell.h / 3.0)
+            .clone();
+        let counters_width = text.measure(ctx)?.x;
+        let dest_point = Vec2::new(counters_cell.x + counters_cell.w / 4.0 + counters_width / 2.0, counters_cell.y + counters_cell.h / 2.0);
+        canvas.draw(&text, graphics::DrawParam::from(dest_point).color(Color::BLACK));
+
+        let background_mesh = self.background_mesh(ctx)?;
+        canvas.draw(&background_mesh, graphics::DrawParam::default());
+
+        if let Some((notice, _)) = self.notice.as_ref().filter(|(_, since)| since.elapsed().as_secs_f32() < NOTICE_SECONDS) {
+            let text = graphics::Text::new(notice.as_str())
+                .set_font(MAIN_FONT)
+                .set_scale(24.0)
+                .clone();
+            let size = text.measure(ctx)?;
+            let dest_point = Vec2::new(viewport.x + ((viewport.w - size.x) / 2.0).max(0.0), viewport.y + size.y);
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(dest_point.x, dest_point.y, size.x, size.y))
+                    .color(Color::from_rgb(255, 255, 200))
+            );
+            canvas.draw(&text, graphics::DrawParam::from(dest_point).color(Color::BLACK));
+        }
+
+        // length of the block being painted, next to the cell the stroke is at
+        if let Some(((col, row), length)) = self.stroke_block(mouse_position) {
+            let cell = self.board_cell(col, row);
+            let text = graphics::Text::new(format!("{}", length))
+                .set_font(MAIN_FONT)
+                .set_scale((cell_size / 2.0).max(16.0))
+                .clone();
+            let size = text.measure(ctx)?;
+            let dest_point = Vec2::new(cell.right(), cell.y - size.y);
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(dest_point.x, dest_point.y, size.x, size.y))
+                    .color(Color::from_rgb(255, 255, 200))
+            );
+            canvas.draw(&text, graphics::DrawParam::from(dest_point).color(Color::BLACK));
+        }
+
+        if let Some(completion) = &self.completion {
+            self.draw_completion(&mut canvas, completion);
+        }
+        canvas.finish(ctx)
+    }
+}
@@ -0,0 +1,179 @@
+use std::io;
+use std::path::Path;
+use crate::description::LevelDescriptionTemplate;
+use crate::difficulty::Difficulty;
+use crate::format::load_level;
+use crate::game_state::is_solved;
+use crate::profile::{LevelRecord, Profile};
+use crate::save::{default_save_path, SaveGame};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Progress {
+    New,
+    Started,
+    Solved
+}
+
+impl Progress {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Progress::New => "new",
+            Progress::Started => "started",
+            Progress::Solved => "solved"
+        }
+    }
+}
+
+/// What a level browser shows about a level
+pub struct LevelSummary {
+    /// file name without the extension
+    pub name: String,
+    pub title: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    /// `None` until the level is rated, see `difficulty::BackgroundRating`
+    pub difficulty: Option<Difficulty>,
+    pub progress: Progress,
+    pub record: Option<LevelRecord>,
+    pub level: LevelDescriptionTemplate
+}
+
+pub struct PackEntry {
+    pub path: String,
+    /// the message of the error if the level could not be loaded
+    pub summary: Result<LevelSummary, String>
+}
+
+/// Files `load_level` can read, save files are skipped
+pub fn is_level_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|x| x.to_str()).unwrap_or("");
+    let extension = path.extension().and_then(|x| x.to_str());
+    !name.ends_with(".save.yaml") && matches!(extension, Some("yaml") | Some("yml") | Some("non") | Some("xml") | Some("pbn"))
+}
+
+/// Loads every level of the directory, sorted by file name. Rating takes long for hard levels
+/// and is left to the caller.
+pub fn scan(dir: &str, profile: &Profile) -> io::Result<Vec<PackEntry>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && is_level_file(&path) {
+            paths.push(path.to_string_lossy().into_owned());
+        }
+    }
+    paths.sort();
+
+    Ok(paths.into_iter()
+        .map(|path| {
+            let summary = load_level(&path).map(|x| summarize(&path, &x, profile)).map_err(|e| e.to_string());
+            PackEntry { path, summary }
+        })
+        .collect())
+}
+
+fn summarize(level_path: &str, level: &LevelDescriptionTemplate, profile: &Profile) -> LevelSummary {
+    let name = Path::new(level_path).file_stem().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default();
+    LevelSummary {
+        name,
+        title: level.title.clone(),
+        width: level.cols.len(),
+        height: level.rows.len(),
+        difficulty: None,
+        progress: progress(level_path, level, profile),
+        record: profile.record(level).copied(),
+        level: level.clone()
+    }
+}
+
+/// Reads the progress and records again, after a level was played
+pub fn refresh(entries: &mut [PackEntry], profile: &Profile) {
+    for entry in entries.iter_mut() {
+        if let Ok(summary) = entry.summary.as_mut() {
+            summary.progress = progress(&entry.path, &summary.level, profile);
+            summary.record = profile.record(&summary.level).copied();
+        }
+    }
+}
+
+impl LevelSummary {
+    /// The title often gives the picture away, it is only shown once the level is solved
+    pub fn shown_title(&self) -> &str {
+        match &self.title {
+            Some(title) if self.progress == Progress::Solved => title,
+            _ => &self.name
+        }
+    }
+}
+
+/// Solved once the profile has a record of it, otherwise read from the save file next to the level.
+/// A save of another level, or one without a single move, counts as not started.
+pub fn progress(level_path: &str, level: &LevelDescriptionTemplate, profile: &Profile) -> Progress {
+    if profile.record(level).is_some_and(|x| x.is_solved()) {
+        return Progress::Solved;
+    }
+    let game_state = match SaveGame::from_file(&default_save_path(level_path)).and_then(|x| x.restore(level)) {
+        Ok(game_state) => game_state,
+        Err(_) => return Progress::New
+    };
+    if is_solved(game_state.grid(), game_state.lvl_desc()) {
+        Progress::Solved
+    }
+    else if game_state.history().nodes().len() > 1 {
+        Progress::Started
+    }
+    else {
+        // the window saves a level that was only opened and closed again
+        Progress::New
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{CellState, GameState, GameStats};
+
+    #[test]
+    fn test_scan_reads_levels_and_saves() {
+        let dir = std::env::temp_dir().join(format!("nonogram-pack-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let level = LevelDescriptionTemplate {
+            rows: vec![vec![1], vec![1]],
+            cols: vec![vec![2]],
+            ..Default::default()
+        };
+        let level_path = dir.join("a.yaml").to_string_lossy().into_owned();
+        level.to_file(&level_path).unwrap();
+        std::fs::write(dir.join("b.yaml"), "rows: [[1]]\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let mut game_state = GameState::new(level.clone().into());
+        SaveGame::new(&level_path, &level, &game_state).to_file(&default_save_path(&level_path)).unwrap();
+        let mut profile = Profile::default();
+        let mut entries = scan(dir.to_str().unwrap(), &profile).unwrap();
+        let progress_of = |entries: &[PackEntry]| entries[0].summary.as_ref().map(|x| x.progress).unwrap();
+        let opened = progress_of(&entries);
+
+        game_state.set(0, 0, CellState::Filled);
+        SaveGame::new(&level_path, &level, &game_state).to_file(&default_save_path(&level_path)).unwrap();
+        refresh(&mut entries, &profile);
+        let started = progress_of(&entries);
+
+        game_state.set(0, 1, CellState::Filled);
+        SaveGame::new(&level_path, &level, &game_state).to_file(&default_save_path(&level_path)).unwrap();
+        refresh(&mut entries, &profile);
+        let solved = progress_of(&entries);
+
+        std::fs::remove_file(default_save_path(&level_path)).unwrap();
+        profile.add_solved(&level, &GameStats::default());
+        refresh(&mut entries, &profile);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        let summary = entries[0].summary.as_ref().unwrap();
+        assert_eq!((summary.shown_title(), summary.width, summary.height), ("a", 1, 2));
+        assert!(entries[1].summary.is_err());
+        assert_eq!((opened, started, solved), (Progress::New, Progress::Started, Progress::Solved));
+        assert_eq!(summary.progress, Progress::Solved);
+        assert!(summary.record.is_some());
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+use std::io;
+use serde::{Deserialize, Serialize};
+use crate::description::LevelDescriptionTemplate;
+use crate::game_state::GameStats;
+use crate::save::SaveError;
+
+/// What the player achieved on one level
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LevelRecord {
+    pub times_solved: usize,
+    /// the fastest game, a game without help from the AI beats any game with it
+    pub best: GameStats
+}
+
+impl LevelRecord {
+    pub fn is_solved(&self) -> bool {
+        self.times_solved > 0
+    }
+}
+
+/// Records of all levels the player finished, keyed by `LevelDescriptionTemplate::level_id`
+/// so that moving or converting a level file keeps its record
+#[derive(Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub levels: BTreeMap<String, LevelRecord>
+}
+
+/// `~/.nonogram-profile.yaml`, or the working directory when there is no home
+pub fn default_profile_path() -> String {
+    let file = ".nonogram-profile.yaml";
+    match std::env::var("HOME") {
+        Ok(home) => std::path::Path::new(&home).join(file).to_string_lossy().into_owned(),
+        Err(_) => file.to_string()
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour on
+pub fn format_time(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    }
+    else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+fn is_better(game: &GameStats, best: &GameStats) -> bool {
+    match (game.ai_helped, best.ai_helped) {
+        (false, true) => true,
+        (true, false) => false,
+        _ => game.seconds < best.seconds
+    }
+}
+
+impl Profile {
+    /// A missing file is an empty profile
+    pub fn from_file(filepath: &str) -> Result<Self, SaveError> {
+        match std::fs::read_to_string(filepath) {
+            Ok(text) => Ok(serde_yaml::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub fn to_file(&self, filepath: &str) -> Result<(), SaveError> {
+        std::fs::write(filepath, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&self, level: &LevelDescriptionTemplate) -> Option<&LevelRecord> {
+        self.levels.get(&level.level_id())
+    }
+
+    /// Counts a finished game, returns whether it is the new best one
+    pub fn add_solved(&mut self, level: &LevelDescriptionTemplate, game: &GameStats) -> bool {
+        let record = self.levels.entry(level.level_id()).or_insert(LevelRecord { times_solved: 0, best: *game });
+        let new_best = record.times_solved == 0 || is_better(game, &record.best);
+        record.times_solved += 1;
+        if new_best {
+            record.best = *game;
+        }
+        new_best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_best_game_is_kept() {
+        let level = LevelDescriptionTemplate { rows: vec![vec![1]], cols: vec![vec![1]], ..Default::default() };
+        let mut profile = Profile::default();
+        let helped = GameStats { seconds: 10.0, moves: 1, undos: 0, ai_helped: true };
+        let slow = GameStats { seconds: 90.0, moves: 3, undos: 2, ai_helped: false };
+        let fast = GameStats { seconds: 60.0, moves: 1, undos: 0, ai_helped: false };
+
+        assert!(profile.add_solved(&level, &helped));
+        assert!(profile.add_solved(&level, &slow));
+        assert!(!profile.add_solved(&level, &helped));
+        assert!(profile.add_solved(&level, &fast));
+
+        let text = serde_yaml::to_string(&profile).unwrap();
+        let profile: Profile = serde_yaml::from_str(&text).unwrap();
+        let record = profile.record(&level).unwrap();
+        assert_eq!(record.times_solved, 4);
+        assert_eq!((record.best.seconds, record.best.ai_helped), (60.0, false));
+        assert_eq!(format_time(record.best.seconds), "1:00");
+        assert_eq!(format_time(3725.0), "1:02:05");
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::io;
+use serde::{Deserialize, Serialize};
+use crate::description::LevelDescriptionTemplate;
+use crate::game_state::{color_symbol, symbol_color, CellState, GameGridState, GameState, GameStats};
+use crate::grid::Grid;
+use crate::history::UndoTree;
+
+/// Progress of one game: the board, the undo tree and the level it belongs to
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub level_path: String,
+    /// `LevelDescriptionTemplate::level_id` of the level
+    pub level_id: String,
+    /// one string per row: `#` for filled, `x` for crossed and `.` for empty cells
+    pub grid: Vec<String>,
+    pub history: UndoTree,
+    #[serde(default)]
+    pub stats: GameStats
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Parse(serde_yaml::Error),
+    OtherLevel { level_path: String },
+    Corrupted(String)
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "can not access save file: {}", e),
+            SaveError::Parse(e) => write!(f, "malformed save file: {}", e),
+            SaveError::OtherLevel { level_path } => write!(f, "save file belongs to another level than '{}'", level_path),
+            SaveError::Corrupted(message) => write!(f, "corrupted save file: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(e) => Some(e),
+            SaveError::Parse(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for SaveError {
+    fn from(e: serde_yaml::Error) -> Self {
+        SaveError::Parse(e)
+    }
+}
+
+/// `levels/heart.yaml` is saved to `levels/heart.save.yaml`
+pub fn default_save_path(level_path: &str) -> String {
+    std::path::Path::new(level_path).with_extension("save.yaml").to_string_lossy().into_owned()
+}
+
+impl SaveGame {
+    pub fn new(level_path: &str, level: &LevelDescriptionTemplate, game_state: &GameState) -> Self {
+        let grid = (0..game_state.height())
+            .map(|row| game_state.grid().row_to_line(row).into_iter()
+                .map(|x| match x {
+                    CellState::Filled => '#',
+                    CellState::Colored(color) => color_symbol(color),
+                    CellState::Crossed => 'x',
+                    CellState::Empty => '.'
+                })
+                .collect())
+            .collect();
+        Self {
+            level_path: level_path.to_string(),
+            level_id: level.level_id(),
+            grid,
+            history: game_state.history().clone(),
+            stats: *game_state.stats()
+        }
+    }
+
+    pub fn from_file(filepath: &str) -> Result<Self, SaveError> {
+        let text = std::fs::read_to_string(filepath)?;
+        Ok(serde_yaml::from_str(&text)?)
+    }
+
+    pub fn to_file(&self, filepath: &str) -> Result<(), SaveError> {
+        std::fs::write(filepath, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Rebuilds the game, the level must be the one the save was made for
+    pub fn restore(&self, level: &LevelDescriptionTemplate) -> Result<GameState, SaveError> {
+        if self.level_id != level.level_id() {
+            return Err(SaveError::OtherLevel { level_path: self.level_path.clone() });
+        }
+
+        let (width, height) = (level.cols.len(), level.rows.len());
+        if self.grid.len() != height || self.grid.iter().any(|x| x.chars().count() != width) {
+            return Err(SaveError::Corrupted(format!("grid is not {}x{} cells", width, height)));
+        }
+        let mut grid = GameGridState::new(width, height);
+        for (row, line) in self.grid.iter().enumerate() {
+            for (col, symbol) in line.chars().enumerate() {
+                let state = match (symbol, symbol_color(symbol)) {
+                    ('x', _) => CellState::Crossed,
+                    ('.', _) => CellState::Empty,
+                    (_, Some(color)) if (color as usize) < level.colors().len() => CellState::filled(color),
+                    _ => return Err(SaveError::Corrupted(format!("unknown symbol '{}' in row {}", symbol, row + 1)))
+                };
+                grid.set(col, row, state);
+            }
+        }
+
+        if !self.history.is_well_formed() {
+            return Err(SaveError::Corrupted("undo history is broken".to_string()));
+        }
+        let outside = self.history.nodes().iter().flat_map(|x| x.transaction.changes.iter()).any(|x| x.col >= width || x.row >= height);
+        if outside {
+            return Err(SaveError::Corrupted("undo history refers to cells outside the grid".to_string()));
+        }
+
+        Ok(GameState::restore(level.clone().into(), grid, self.history.clone(), self.stats))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_save_and_restore() {
+        let level = LevelDescriptionTemplate {
+            rows: vec![vec![1], vec![2]],
+            cols: vec![vec![2], vec![1]],
+            ..Default::default()
+        };
+        let mut game_state = GameState::new(level.clone().into());
+        game_state.set(0, 1, CellState::Filled);
+        game_state.set(1, 1, CellState::Filled);
+
+        let text = serde_yaml::to_string(&SaveGame::new("level.yaml", &level, &game_state)).unwrap();
+        let save: SaveGame = serde_yaml::from_str(&text).unwrap();
+        let mut restored = save.restore(&level).unwrap();
+        assert_eq!(restored.grid().row_to_line(1), vec![CellState::Filled, CellState::Filled]);
+        assert!(restored.lvl_desc().rows[1].parts[0].is_completed);
+
+        restored.undo();
+        restored.undo();
+        assert_eq!(restored.get(1, 1), CellState::Empty);
+        restored.redo();
+        restored.redo();
+        assert_eq!(restored.get(1, 1), CellState::Filled);
+
+        let other = LevelDescriptionTemplate { rows: vec![vec![2], vec![1]], cols: vec![vec![2], vec![1]], ..Default::default() };
+        assert!(matches!(save.restore(&other), Err(SaveError::OtherLevel { .. })));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::description::LevelDescription;
+use crate::game_state::{CellState, GameGridState};
+use crate::grid::Grid;
+use crate::strategy::line_solver::propagate;
+
+pub enum Solutions {
+    None,
+    Unique(GameGridState),
+    Multiple(Vec<GameGridState>)
+}
+
+impl Solutions {
+    pub fn count(&self) -> usize {
+        match self {
+            Solutions::None => 0,
+            Solutions::Unique(_) => 1,
+            Solutions::Multiple(solutions) => solutions.len()
+        }
+    }
+}
+
+/// Depth-first search over undecided cells with line solving after every guess.
+pub struct Solver {
+    /// search stops as soon as this many solutions are collected,
+    /// it is never less than 2 so a unique solution can be told apart
+    pub max_solutions: usize
+}
+
+impl Solver {
+    pub fn new(max_solutions: usize) -> Self {
+        Self { max_solutions: max_solutions.max(2) }
+    }
+
+    pub fn solve(&self, level_description: &LevelDescription) -> Solutions {
+        let grid = GameGridState::new(level_description.cols.len(), level_description.rows.len());
+        self.solve_from(level_description, &grid)
+    }
+
+    /// Looks only for solutions that agree with the cells already decided in `grid`
+    pub fn solve_from<T: Grid + ?Sized>(&self, level_description: &LevelDescription, grid: &T) -> Solutions {
+        let mut found = Vec::new();
+        self.search(level_description, GameGridState::copy_of(grid), &mut found);
+
+        match found.len() {
+            0 => Solutions::None,
+            1 => Solutions::Unique(found.remove(0)),
+            _ => Solutions::Multiple(found)
+        }
+    }
+
+    fn search(&self, level_description: &LevelDescription, mut grid: GameGridState, found: &mut Vec<GameGridState>) {
+        if found.len() >= self.max_solutions || !propagate(level_description, &mut grid) {
+            return;
+        }
+
+        let undecided = grid.iter().find(|(_, _, cell)| *cell == CellState::Empty);
+        match undecided {
+            // every line passed the line solver, so a fully decided grid is a solution
+            None => found.push(grid),
+            Some((col, row, _)) => {
+                for state in level_description.cell_candidates(col, row) {
+                    let mut branch = grid.clone();
+                    branch.set(col, row, state);
+                    self.search(level_description, branch, found);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::description::LevelDescriptionTemplate;
+
+    fn level(rows: Vec<Vec<usize>>, cols: Vec<Vec<usize>>) -> LevelDescription {
+        LevelDescriptionTemplate { rows, cols, ..Default::default() }.into()
+    }
+
+    #[test]
+    fn test_unique_solution() {
+        let heart = level(
+            vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]],
+            vec![vec![2], vec![4], vec![4], vec![4], vec![2]]
+        );
+        match Solver::new(2).solve(&heart) {
+            Solutions::Unique(grid) => {
+                assert_eq!(grid.row_to_line(0), vec![CellState::Crossed, CellState::Filled, CellState::Crossed, CellState::Filled, CellState::Crossed]);
+            },
+            _ => panic!("heart has exactly one solution")
+        }
+    }
+
+    #[test]
+    fn test_multiple_and_no_solutions() {
+        let ambiguous = level(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+        assert_eq!(Solver::new(10).solve(&ambiguous).count(), 2);
+
+        let broken = level(vec![vec![2], vec![]], vec![vec![1], vec![]]);
+        assert_eq!(Solver::new(10).solve(&broken).count(), 0);
+    }
+
+    #[test]
+    fn test_colored_solution() {
+        use crate::description::PaletteColor;
+        // a red block touching a green one in the first row
+        let flag = LevelDescriptionTemplate {
+            palette: vec![PaletteColor::new("red", "f00"), PaletteColor::new("green", "0f0")],
+            rows: vec![vec![1, 1], vec![1]],
+            row_colors: vec![vec![0, 1], vec![0]],
+            cols: vec![vec![2], vec![1]],
+            col_colors: vec![vec![0], vec![1]],
+            ..Default::default()
+        };
+        match Solver::new(2).solve(&flag.into()) {
+            Solutions::Unique(grid) => {
+                assert_eq!(grid.row_to_line(0), vec![CellState::Filled, CellState::Colored(1)]);
+                assert_eq!(grid.row_to_line(1), vec![CellState::Filled, CellState::Crossed]);
+            },
+            _ => panic!("the flag has exactly one solution")
+        }
+    }
+}
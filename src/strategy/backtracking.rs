@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+use crate::description::LevelDescription;
+use crate::game_state::{is_solved, CellState, GameGridState};
+use crate::grid::Grid;
+use crate::solver::{Solutions, Solver};
+use crate::strategy::GridSolvingStrategy;
+
+/// Last resort: searches for the solution and reveals one undecided cell of it.
+/// Makes no decision if the puzzle has no solution or more than one.
+/// The solution is kept, the next cells are revealed without searching again.
+#[derive(Default)]
+pub struct BacktrackingStrategy {
+    found: RefCell<Option<FoundSolution>>
+}
+
+/// The only solution of the level for the grid the search started from
+struct FoundSolution {
+    start: GameGridState,
+    solution: GameGridState
+}
+
+impl FoundSolution {
+    /// A grid that keeps the cells of `start` and agrees with the solution has no other solution
+    fn holds_for(&self, level_description: &LevelDescription, grid: &dyn Grid) -> bool {
+        if grid.width() != self.solution.width() || grid.height() != self.solution.height() {
+            return false;
+        }
+        let agrees = (0..grid.height()).all(|row| (0..grid.width()).all(|col| {
+            let cell = grid.get(col, row);
+            let start = self.start.get(col, row);
+            (start == CellState::Empty || start == cell)
+                && (cell == CellState::Empty || cell == self.solution.get(col, row))
+        }));
+        agrees && is_solved(&self.solution, level_description)
+    }
+}
+
+impl GridSolvingStrategy for BacktrackingStrategy {
+
+    fn process_grid(&self, level_description: &LevelDescription, grid: &mut dyn Grid) -> bool {
+        let mut found = self.found.borrow_mut();
+        if !found.as_ref().is_some_and(|x| x.holds_for(level_description, grid)) {
+            *found = match Solver::new(2).solve_from(level_description, grid) {
+                Solutions::Unique(solution) => Some(FoundSolution { start: GameGridState::copy_of(grid), solution }),
+                _ => None
+            };
+        }
+        let solution = match found.as_ref() {
+            Some(found) => &found.solution,
+            None => return false
+        };
+
+        for (col, row, cell) in solution.iter() {
+            if grid.get(col, row) == CellState::Empty {
+                grid.set(col, row, cell);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::description::LevelDescriptionTemplate;
+    use crate::game_state::GameState;
+    use crate::transaction::TransactionBuilder;
+
+    #[test]
+    fn test_backtracking_reveals_the_solution_one_cell_at_a_time() {
+        let game_state = GameState::new(LevelDescriptionTemplate {
+            rows: vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]],
+            cols: vec![vec![2], vec![4], vec![4], vec![4], vec![2]],
+            ..Default::default()
+        }.into());
+        let mut grid = TransactionBuilder::new(game_state.grid());
+        let strategy = BacktrackingStrategy::default();
+
+        let mut revealed = 0;
+        while strategy.process_grid(game_state.lvl_desc(), &mut grid) {
+            revealed += 1;
+        }
+        assert_eq!(revealed, 25);
+        assert!(is_solved(&grid, game_state.lvl_desc()));
+    }
+
+    #[test]
+    fn test_backtracking_searches_again_after_a_wrong_cell() {
+        let game_state = GameState::new(LevelDescriptionTemplate {
+            rows: vec![vec![1], vec![2]],
+            cols: vec![vec![2], vec![1]],
+            ..Default::default()
+        }.into());
+        let mut grid = TransactionBuilder::new(game_state.grid());
+        let strategy = BacktrackingStrategy::default();
+
+        assert!(strategy.process_grid(game_state.lvl_desc(), &mut grid));
+        // the top right cell is empty in the solution, no solution is left once it is filled
+        grid.set(1, 0, CellState::Filled);
+        assert!(!strategy.process_grid(game_state.lvl_desc(), &mut grid));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::description::{Clue, LevelDescription, LineDescription};
+use crate::game_state::{CellState, ColorId};
+use crate::grid::Grid;
+use crate::line::Line;
+use crate::strategy::SolvingStrategy;
+
+/// Finds every cell that has the same colour in all arrangements of the line
+/// consistent with the current cells, or is Crossed in all of them.
+pub struct LineSolverStrategy {
+
+}
+
+impl SolvingStrategy for LineSolverStrategy {
+
+    fn process_one_line(&self, line_description: &LineDescription, line: &mut dyn Line) -> bool {
+        let clues = line_description.clues();
+        let cells: Vec<CellState> = (0..line.len()).map(|i| line.get(i)).collect();
+
+        let solved = match solve_line(&clues, &cells) {
+            Some(solved) => solved,
+            None => return false
+        };
+
+        let mut changed = false;
+        for (pos, cell) in solved.into_iter().enumerate() {
+            if cells[pos] == CellState::Empty && cell != CellState::Empty {
+                line.set(pos, cell);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Returns the line with every cell decided that is the same in all valid arrangements,
+/// or `None` if the clues can not be placed on the line at all.
+/// Blocks of the same colour need a crossed cell between them, blocks of different colours may touch.
+pub fn solve_line(clues: &[Clue], cells: &[CellState]) -> Option<Vec<CellState>> {
+    let n = cells.len();
+    let k = clues.len();
+    let colors = clues.iter().map(|(_, color)| *color as usize + 1).max().unwrap_or(0);
+    let is_background = |cell: CellState| cell == CellState::Empty || cell == CellState::Crossed;
+
+    // blocked_before[j][i] - number of cells in cells[..i] the block `j` can not cover
+    let blocked_before: Vec<Vec<usize>> = clues.iter()
+        .map(|(_, color)| {
+            let mut result = vec![0; n + 1];
+            for (i, cell) in cells.iter().enumerate() {
+                let blocked = *cell != CellState::Empty && *cell != CellState::filled(*color);
+                result[i + 1] = result[i] + usize::from(blocked);
+            }
+            result
+        })
+        .collect();
+    // position right after the block `j` placed at `i` and the gap it needs, or None if it can not be placed there
+    let block_end = |i: usize, j: usize| -> Option<usize> {
+        let end = i + clues[j].0;
+        if end > n || blocked_before[j][end] != blocked_before[j][i] {
+            return None;
+        }
+        // a block of another colour or the background has to follow, both are checked by the next state
+        let needs_gap = clues.get(j + 1).is_some_and(|next| next.1 == clues[j].1);
+        if !needs_gap {
+            Some(end)
+        }
+        else if end < n && is_background(cells[end]) {
+            Some(end + 1)
+        }
+        else {
+            None
+        }
+    };
+
+    // fits[i][j] - blocks j.. can be placed in cells[i..]
+    let mut fits = vec![vec![false; k + 1]; n + 1];
+    fits[n][k] = true;
+    for i in (0..n).rev() {
+        for j in 0..=k {
+            let skip = is_background(cells[i]) && fits[i + 1][j];
+            let place = j < k && block_end(i, j).is_some_and(|next| fits[next][j + 1]);
+            fits[i][j] = skip || place;
+        }
+    }
+    if !fits[0][0] {
+        return None;
+    }
+
+    // reached[i][j] - first j blocks can be placed in cells[..i] leaving the rest valid
+    let mut reached = vec![vec![false; k + 1]; n + 1];
+    reached[0][0] = true;
+    let mut can_be_crossed = vec![false; n];
+    // difference arrays of cells covered by some valid block position, one per colour
+    let mut filled_delta = vec![vec![0i32; n + 1]; colors];
+
+    for i in 0..n {
+        for j in 0..=k {
+            if !reached[i][j] {
+                continue;
+            }
+            if is_background(cells[i]) && fits[i + 1][j] {
+                can_be_crossed[i] = true;
+                reached[i + 1][j] = true;
+            }
+            if j < k {
+                if let Some(next) = block_end(i, j).filter(|next| fits[*next][j + 1]) {
+                    let (count, color) = clues[j];
+                    filled_delta[color as usize][i] += 1;
+                    filled_delta[color as usize][i + count] -= 1;
+                    if next > i + count {
+                        can_be_crossed[i + count] = true;
+                    }
+                    reached[next][j + 1] = true;
+                }
+            }
+        }
+    }
+
+    let mut covered = vec![0; colors];
+    let mut result = cells.to_vec();
+    for (i, cell) in result.iter_mut().enumerate() {
+        let mut possible = Vec::new();
+        for (color, delta) in filled_delta.iter().enumerate() {
+            covered[color] += delta[i];
+            if covered[color] > 0 {
+                possible.push(color as ColorId);
+            }
+        }
+        match possible[..] {
+            [color] if !can_be_crossed[i] => *cell = CellState::filled(color),
+            [] if can_be_crossed[i] => *cell = CellState::Crossed,
+            _ => {}
+        }
+    }
+    Some(result)
+}
+
+/// Runs `solve_line` over rows and columns of the grid until nothing changes.
+/// Returns false if some line has no valid arrangement left.
+pub fn propagate(level_description: &LevelDescription, grid: &mut dyn Grid) -> bool {
+    let mut dirty_rows = vec![true; grid.height()];
+    let mut dirty_cols = vec![true; grid.width()];
+
+    while dirty_rows.iter().any(|x| *x) || dirty_cols.iter().any(|x| *x) {
+        for (row, dirty) in dirty_rows.iter_mut().enumerate() {
+            if !std::mem::take(dirty) {
+                continue;
+            }
+            let clues = level_description.rows[row].clues();
+            let cells = grid.row_to_line(row);
+            let solved = match solve_line(&clues, &cells) {
+                Some(solved) => solved,
+                None => return false
+            };
+            for (col, cell) in solved.into_iter().enumerate() {
+                if cells[col] != cell {
+                    grid.set(col, row, cell);
+                    dirty_cols[col] = true;
+                }
+            }
+        }
+
+        for (col, dirty) in dirty_cols.iter_mut().enumerate() {
+            if !std::mem::take(dirty) {
+                continue;
+            }
+            let clues = level_description.cols[col].clues();
+            let cells = grid.col_to_line(col);
+            let solved = match solve_line(&clues, &cells) {
+                Some(solved) => solved,
+                None => return false
+            };
+            for (row, cell) in solved.into_iter().enumerate() {
+                if cells[row] != cell {
+                    grid.set(col, row, cell);
+                    dirty_rows[row] = true;
+                }
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::description::monochrome;
+    use CellState::*;
+
+    #[test]
+    fn test_solve_line_finds_common_cells() {
+        let line = vec![Empty, Empty, Empty, Empty, Filled, Empty, Empty, Empty];
+        let result = solve_line(&monochrome(&[1, 3]), &line).unwrap();
+        assert_eq!(result, vec![Empty, Empty, Empty, Empty, Filled, Empty, Empty, Crossed]);
+
+        let line = vec![Empty, Filled, Empty, Empty, Empty, Empty, Empty, Filled];
+        let result = solve_line(&monochrome(&[2, 3]), &line).unwrap();
+        assert_eq!(result, vec![Empty, Filled, Empty, Crossed, Crossed, Filled, Filled, Filled]);
+    }
+
+    #[test]
+    fn test_solve_line_beyond_overlap() {
+        // block of 1 already found can only be the first clue
+        let line = vec![Filled, Empty, Empty, Empty, Empty, Empty];
+        let result = solve_line(&monochrome(&[1, 2]), &line).unwrap();
+        assert_eq!(result, vec![Filled, Crossed, Empty, Empty, Empty, Empty]);
+    }
+
+    #[test]
+    fn test_solve_line_detects_contradiction() {
+        let line = vec![Filled, Filled, Filled, Empty];
+        assert!(solve_line(&monochrome(&[2]), &line).is_none());
+        assert_eq!(solve_line(&monochrome(&[]), &[Crossed, Empty]), Some(vec![Crossed, Crossed]));
+    }
+
+    #[test]
+    fn test_solve_line_with_colors() {
+        // different colours touch, the same colour needs a gap
+        let line = vec![Empty; 3];
+        let result = solve_line(&[(2, 1), (1, 2)], &line).unwrap();
+        assert_eq!(result, vec![Colored(1), Colored(1), Colored(2)]);
+        assert!(solve_line(&[(2, 1), (1, 1)], &line).is_none());
+
+        // the second block is red wherever it goes
+        let line = vec![Empty, Empty, Empty, Empty];
+        let result = solve_line(&[(1, 0), (2, 1)], &line).unwrap();
+        assert_eq!(result, vec![Empty, Empty, Colored(1), Empty]);
+
+        // a black cell can only be the first block
+        let line = vec![Empty, Empty, Filled, Empty, Empty];
+        let result = solve_line(&[(1, 0), (2, 1)], &line).unwrap();
+        assert_eq!(result, vec![Crossed, Crossed, Filled, Colored(1), Colored(1)]);
+    }
+}
@@ -1,9 +1,71 @@
+use std::fmt;
+use std::str::FromStr;
 use crate::description::{LevelDescription, LineDescription};
 use crate::grid::Grid;
 use crate::line::Line;
 
 pub mod simple;
+pub mod overlap;
+pub mod line_solver;
+pub mod probing;
+pub mod backtracking;
 
 pub trait SolvingStrategy {
     fn process_one_line(&self, level_description: &LineDescription, line: &mut dyn Line) -> bool;
 }
+
+/// Strategy that needs to see the whole grid instead of a single line
+pub trait GridSolvingStrategy {
+    fn process_grid(&self, level_description: &LevelDescription, grid: &mut dyn Grid) -> bool;
+}
+
+/// Every strategy the game knows, from the weakest to the strongest
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StrategyKind {
+    Simple,
+    Overlap,
+    Line,
+    Probing,
+    Backtracking
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 5] = [
+        StrategyKind::Simple,
+        StrategyKind::Overlap,
+        StrategyKind::Line,
+        StrategyKind::Probing,
+        StrategyKind::Backtracking
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrategyKind::Simple => "simple",
+            StrategyKind::Overlap => "overlap",
+            StrategyKind::Line => "line",
+            StrategyKind::Probing => "probing",
+            StrategyKind::Backtracking => "backtracking"
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StrategyKind::ALL.iter()
+            .find(|x| x.name() == s)
+            .copied()
+            .ok_or_else(|| format!(
+                "unknown strategy '{}', expected one of: {}",
+                s,
+                StrategyKind::ALL.map(|x| x.name()).join(", ")
+            ))
+    }
+}
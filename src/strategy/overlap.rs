@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use crate::description::{Clue, LineDescription};
+use crate::game_state::CellState;
+use crate::line::Line;
+use crate::strategy::SolvingStrategy;
+
+/// Packs every block as far left and as far right as the line allows,
+/// fills the overlap of both positions and crosses cells no block can reach.
+pub struct OverlapStrategy {
+
+}
+
+impl SolvingStrategy for OverlapStrategy {
+
+    fn process_one_line(&self, line_description: &LineDescription, line: &mut dyn Line) -> bool {
+        let clues = line_description.clues();
+        let cells: Vec<CellState> = (0..line.len()).map(|i| line.get(i)).collect();
+
+        let (left, right) = match (leftmost_placement(&clues, &cells), rightmost_placement(&clues, &cells)) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return false
+        };
+
+        let mut reachable = vec![false; cells.len()];
+        let mut changed = false;
+
+        for (i, (count, color)) in clues.iter().enumerate() {
+            reachable[left[i]..right[i] + count].fill(true);
+            for pos in right[i]..left[i] + count {
+                if line.get(pos) == CellState::Empty {
+                    line.set(pos, CellState::filled(*color));
+                    changed = true;
+                }
+            }
+        }
+
+        for (pos, cell) in cells.iter().enumerate() {
+            if !reachable[pos] && *cell == CellState::Empty {
+                line.set(pos, CellState::Crossed);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Start positions of every block when packed as far left as possible,
+/// or `None` if the clues do not fit the line at all.
+pub fn leftmost_placement(clues: &[Clue], cells: &[CellState]) -> Option<Vec<usize>> {
+    let mut starts = Vec::with_capacity(clues.len());
+    let mut dead_ends = HashSet::new();
+    if place_from(clues, cells, 0, 0, &mut starts, &mut dead_ends) {
+        Some(starts)
+    }
+    else {
+        None
+    }
+}
+
+/// Start positions of every block when packed as far right as possible.
+pub fn rightmost_placement(clues: &[Clue], cells: &[CellState]) -> Option<Vec<usize>> {
+    let reversed_clues: Vec<Clue> = clues.iter().rev().copied().collect();
+    let reversed_cells: Vec<CellState> = cells.iter().rev().copied().collect();
+    let reversed_starts = leftmost_placement(&reversed_clues, &reversed_cells)?;
+    Some(reversed_starts.iter()
+        .zip(reversed_clues.iter())
+        .rev()
+        .map(|(start, (count, _))| cells.len() - start - count)
+        .collect())
+}
+
+fn place_from(
+    clues: &[Clue],
+    cells: &[CellState],
+    block: usize,
+    from: usize,
+    starts: &mut Vec<usize>,
+    dead_ends: &mut HashSet<(usize, usize)>
+) -> bool {
+    if block == clues.len() {
+        return cells[from.min(cells.len())..].iter().all(|x| !x.is_filled());
+    }
+    if dead_ends.contains(&(block, from)) {
+        return false;
+    }
+
+    let (count, color) = clues[block];
+    // blocks of the same colour need a gap between them, blocks of different colours may touch
+    let gap = usize::from(clues.get(block + 1).is_some_and(|next| next.1 == color));
+    let mut start = from;
+    while start + count <= cells.len() {
+        let fits = cells[start..start + count].iter().all(|x| *x == CellState::Empty || *x == CellState::filled(color))
+            && cells.get(start + count) != Some(&CellState::filled(color))
+            && (gap == 0 || cells.get(start + count).is_none_or(|x| !x.is_filled()));
+        if fits {
+            starts.push(start);
+            if place_from(clues, cells, block + 1, start + count + gap, starts, dead_ends) {
+                return true;
+            }
+            starts.pop();
+        }
+        // a filled cell can not be left behind the block
+        if cells[start].is_filled() {
+            break;
+        }
+        start += 1;
+    }
+
+    dead_ends.insert((block, from));
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::description::LineDescriptionPart;
+    use CellState::*;
+
+    struct TestLine(Vec<CellState>);
+
+    impl Line for TestLine {
+        fn get(&self, pos: usize) -> CellState { self.0[pos] }
+        fn set(&mut self, pos: usize, cell_state: CellState) { self.0[pos] = cell_state }
+        fn len(&self) -> usize { self.0.len() }
+    }
+
+    fn description(clues: &[usize]) -> LineDescription {
+        LineDescription {
+            parts: clues.iter().map(|x| LineDescriptionPart { elements_count: *x, color: 0, is_completed: false }).collect()
+        }
+    }
+
+    #[test]
+    fn test_overlap_of_long_block() {
+        let mut line = TestLine(vec![Empty; 10]);
+        assert!(OverlapStrategy {}.process_one_line(&description(&[8]), &mut line));
+        assert_eq!(line.0, vec![Empty, Empty, Filled, Filled, Filled, Filled, Filled, Filled, Empty, Empty]);
+    }
+
+    #[test]
+    fn test_crosses_unreachable_cells() {
+        let mut line = TestLine(vec![Empty, Empty, Empty, Crossed, Empty, Filled, Empty, Empty]);
+        assert!(OverlapStrategy {}.process_one_line(&description(&[2]), &mut line));
+        assert_eq!(line.0, vec![Crossed, Crossed, Crossed, Crossed, Empty, Filled, Empty, Crossed]);
+    }
+
+    #[test]
+    fn test_no_change_reports_false() {
+        let mut line = TestLine(vec![Empty; 10]);
+        assert!(!OverlapStrategy {}.process_one_line(&description(&[2, 2]), &mut line));
+        assert_eq!(line.0, vec![Empty; 10]);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::description::LevelDescription;
+use crate::game_state::CellState;
+use crate::grid::Grid;
+use crate::strategy::GridSolvingStrategy;
+use crate::strategy::line_solver::propagate;
+use crate::transaction::TransactionBuilder;
+
+/// Tries every state of an undecided cell, each colour and crossed, on a scratch copy of the grid
+/// and keeps whatever all tries agree on. When only one try avoids a contradiction
+/// it decides the cell right away.
+pub struct ProbingStrategy {
+
+}
+
+impl GridSolvingStrategy for ProbingStrategy {
+
+    fn process_grid(&self, level_description: &LevelDescription, grid: &mut dyn Grid) -> bool {
+        for row in 0..grid.height() {
+            for col in 0..grid.width() {
+                if grid.get(col, row) != CellState::Empty {
+                    continue;
+                }
+
+                let tries: Vec<(CellState, TransactionBuilder)> = level_description.cell_candidates(col, row).into_iter()
+                    .filter_map(|state| probe(level_description, grid, col, row, state).map(|x| (state, x)))
+                    .collect();
+
+                match &tries[..] {
+                    [] => return false,
+                    [(state, _)] => {
+                        grid.set(col, row, *state);
+                        return true;
+                    },
+                    [(_, first), others @ ..] => {
+                        let mut changed = false;
+                        for (x, y, cell) in first.iter() {
+                            if cell != CellState::Empty && others.iter().all(|(_, other)| other.get(x, y) == cell) && grid.get(x, y) == CellState::Empty {
+                                grid.set(x, y, cell);
+                                changed = true;
+                            }
+                        }
+                        if changed {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Scratch copy of the grid with one cell set and line solving propagated to a fixed point,
+/// or `None` if that leads to a contradiction.
+fn probe(level_description: &LevelDescription, grid: &dyn Grid, col: usize, row: usize, state: CellState) -> Option<TransactionBuilder> {
+    let mut scratch = TransactionBuilder::new(grid);
+    scratch.set(col, row, state);
+    if propagate(level_description, &mut scratch) {
+        Some(scratch)
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::description::LevelDescriptionTemplate;
+    use crate::game_state::GameState;
+
+    #[test]
+    fn test_probing_progresses_where_line_solving_is_stuck() {
+        let game_state = GameState::new(LevelDescriptionTemplate {
+            rows: vec![vec![2], vec![1], vec![2], vec![1], vec![1, 1]],
+            cols: vec![vec![2], vec![1, 1], vec![1, 1], vec![1], vec![1]],
+            ..Default::default()
+        }.into());
+        let mut grid = TransactionBuilder::new(game_state.grid());
+
+        assert!(propagate(game_state.lvl_desc(), &mut grid));
+        assert!(grid.iter().all(|(_, _, cell)| cell == CellState::Empty));
+
+        assert!(ProbingStrategy {}.process_grid(game_state.lvl_desc(), &mut grid));
+        assert!(grid.iter().any(|(_, _, cell)| cell != CellState::Empty));
+    }
+}
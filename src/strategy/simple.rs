@@ -1,6 +1,5 @@
-use crate::description::{LevelDescription, LineDescription};
+use crate::description::LineDescription;
 use crate::game_state::CellState;
-use crate::grid::Grid;
 use crate::line::Line;
 use crate::strategy::SolvingStrategy;
 
@@ -14,23 +13,23 @@ impl SolvingStrategy for SimpleStrategy {
         let begin_offset = (0..line.len()).take_while(|i| line.get(*i) == CellState::Crossed).count();
         let end_offset = (0..line.len()).rev().take_while(|i| line.get(*i) == CellState::Crossed).count();
 
-        let total_filled_count = line_description.parts.iter().map(|x| x.elements_count).sum::<usize>();
-        let total_crossed_count = line_description.parts.len() - 1;
+        let parts = &line_description.parts;
+        let total_filled_count = parts.iter().map(|x| x.elements_count).sum::<usize>();
+        // only blocks of the same colour are kept apart by a crossed cell
+        let total_crossed_count = parts.windows(2).filter(|x| x[0].color == x[1].color).count();
         let current_emplace_target_count = line.len() - begin_offset - end_offset;
 
         if total_filled_count + total_crossed_count == current_emplace_target_count {
             let mut index = begin_offset;
-            for element_index in 0..line_description.parts.len() - 1 {
-                for i in 0..line_description.parts[element_index].elements_count {
-                    line.set(index, CellState::Filled);
+            for (element_index, part) in parts.iter().enumerate() {
+                for _ in 0..part.elements_count {
+                    line.set(index, CellState::filled(part.color));
+                    index += 1;
+                }
+                if parts.get(element_index + 1).is_some_and(|x| x.color == part.color) {
+                    line.set(index, CellState::Crossed);
                     index += 1;
                 }
-                line.set(index, CellState::Crossed);
-                index += 1;
-            }
-            for i in 0..line_description.parts[line_description.parts.len() - 1].elements_count {
-                line.set(index, CellState::Filled);
-                index += 1;
             }
             return true;
         }
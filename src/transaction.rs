@@ -1,10 +1,8 @@
-use std::cell::Cell;
-use std::iter::{FlatMap, IntoIterator, Map};
-use std::ops::Range;
-use crate::game_state::{CellState, GameGridState, GameState};
+use serde::{Deserialize, Serialize};
+use crate::game_state::CellState;
 use crate::grid::Grid;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TransactionDetails {
     pub col: usize,
     pub row: usize,
@@ -12,7 +10,7 @@ pub struct TransactionDetails {
     pub new_state: CellState
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub changes: Vec<TransactionDetails>
 }
@@ -24,6 +22,16 @@ impl Transaction {
     fn set(&mut self, col: usize, row: usize, new_state: CellState, old_state: CellState) {
         self.changes.push(TransactionDetails {col, row, old_state, new_state})
     }
+    /// Appends a later transaction, a cell changed by both keeps its first old state and its last new state
+    pub fn merge(&mut self, other: &Transaction) {
+        for change in other.changes.iter() {
+            match self.changes.iter().position(|x| x.col == change.col && x.row == change.row) {
+                Some(i) if self.changes[i].old_state == change.new_state => { self.changes.remove(i); },
+                Some(i) => self.changes[i].new_state = change.new_state,
+                None => self.changes.push(change.clone())
+            }
+        }
+    }
 }
 pub struct TransactionBuilder {
     grid: Vec<Vec<CellState>>,
@@ -38,7 +46,7 @@ impl Grid for TransactionBuilder {
 }
 
 impl TransactionBuilder {
-    pub fn new<T: Grid>(target: &T) -> Self {
+    pub fn new<T: Grid + ?Sized>(target: &T) -> Self {
         let mut grid = vec![vec![CellState::Empty; target.width()]; target.height()];
         for col in 0..target.width() {
             for row in 0..target.height() {
@@ -47,7 +55,7 @@ impl TransactionBuilder {
         }
         Self { grid, height: target.height(), width: target.width() }
     }
-    pub fn to_transaction<T: Grid>(&self, target: &T) -> Transaction {
+    pub fn to_transaction<T: Grid + ?Sized>(&self, target: &T) -> Transaction {
         let mut transaction = Transaction::new();
         for col in 0..target.width() {
             for row in 0..target.height() {
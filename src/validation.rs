@@ -0,0 +1,328 @@
+use std::fmt;
+use std::io;
+use crate::description::{Clue, LevelDescriptionTemplate, Solution};
+use crate::game_state::MAX_COLORS;
+use crate::generator::level_from_picture;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineId {
+    Row(usize),
+    Col(usize)
+}
+
+impl fmt::Display for LineId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineId::Row(i) => write!(f, "row {}", i + 1),
+            LineId::Col(i) => write!(f, "column {}", i + 1)
+        }
+    }
+}
+
+/// One concrete problem with the clues of a level.
+/// `source_line` is the 1-based line of the level file the clue list was read from, when known.
+#[derive(PartialEq, Debug)]
+pub enum ValidationError {
+    NoRows,
+    NoCols,
+    TotalsMismatch { rows_total: usize, cols_total: usize },
+    ZeroLengthClue { line: LineId, source_line: Option<usize> },
+    LineTooShort { line: LineId, required: usize, available: usize, source_line: Option<usize> },
+    SolutionSymbol { row: usize, col: usize },
+    SolutionSize { solution_width: usize, solution_height: usize, width: usize, height: usize },
+    SolutionMismatch { line: LineId },
+    TooManyColors { colors: usize },
+    PaletteColor { name: String },
+    ColorCount { line: LineId, clues: usize, colors: usize },
+    UnknownColor { line: LineId, color: usize },
+    ColorTotalsMismatch { color: String, rows_total: usize, cols_total: usize },
+    ColoredSolution
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NoRows => write!(f, "the level has no rows"),
+            ValidationError::NoCols => write!(f, "the level has no columns"),
+            ValidationError::TotalsMismatch { rows_total, cols_total } =>
+                write!(f, "row clues add up to {} cells but column clues add up to {}", rows_total, cols_total),
+            ValidationError::ZeroLengthClue { line, source_line } => {
+                write_source_line(f, source_line)?;
+                write!(f, "{} has a zero-length clue, use an empty list for an empty line", line)
+            },
+            ValidationError::LineTooShort { line, required, available, source_line } => {
+                write_source_line(f, source_line)?;
+                write!(f, "{} needs at least {} cells for its clues but has only {}", line, required, available)
+            },
+            ValidationError::SolutionSymbol { row, col } =>
+                write!(f, "solution has an unknown symbol in row {}, column {}", row + 1, col + 1),
+            ValidationError::SolutionSize { solution_width, solution_height, width, height } =>
+                write!(f, "solution is {}x{} cells but the level is {}x{}", solution_width, solution_height, width, height),
+            ValidationError::SolutionMismatch { line } =>
+                write!(f, "{} of the solution does not match its clues", line),
+            ValidationError::TooManyColors { colors } =>
+                write!(f, "the palette has {} colours, at most {} are supported", colors, MAX_COLORS),
+            ValidationError::PaletteColor { name } =>
+                write!(f, "colour {} is not a hex colour like ff8000", name),
+            ValidationError::ColorCount { line, clues, colors } =>
+                write!(f, "{} has {} clues but {} colours", line, clues, colors),
+            ValidationError::UnknownColor { line, color } =>
+                write!(f, "{} uses colour {} which is not in the palette", line, color),
+            ValidationError::ColorTotalsMismatch { color, rows_total, cols_total } =>
+                write!(f, "{} row clues add up to {} cells but {} column clues add up to {}", color, rows_total, color, cols_total),
+            ValidationError::ColoredSolution =>
+                write!(f, "solutions of coloured levels are not supported, leave the solution out")
+        }
+    }
+}
+
+fn write_source_line(f: &mut fmt::Formatter<'_>, source_line: &Option<usize>) -> fmt::Result {
+    match source_line {
+        Some(source_line) => write!(f, "line {}: ", source_line),
+        None => Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Parse { line: Option<usize>, message: String },
+    Invalid(Vec<ValidationError>)
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "can not read level file: {}", e),
+            LevelError::Parse { line: Some(line), message } => write!(f, "malformed level file, line {}: {}", line, message),
+            LevelError::Parse { line: None, message } => write!(f, "malformed level file: {}", message),
+            LevelError::Invalid(errors) => {
+                write!(f, "invalid level:")?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelError::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(e: io::Error) -> Self {
+        LevelError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for LevelError {
+    fn from(e: serde_yaml::Error) -> Self {
+        LevelError::Parse { line: e.location().map(|x| x.line()), message: e.to_string() }
+    }
+}
+
+/// Checks the clues of the level, `row_lines` and `col_lines` map each clue list
+/// to the line of the source file it came from and may be shorter than the lists.
+pub fn validate(level: &LevelDescriptionTemplate, row_lines: &[usize], col_lines: &[usize]) -> Result<(), LevelError> {
+    let mut errors = Vec::new();
+
+    if level.rows.is_empty() {
+        errors.push(ValidationError::NoRows);
+    }
+    if level.cols.is_empty() {
+        errors.push(ValidationError::NoCols);
+    }
+
+    let rows_total: usize = level.rows.iter().flatten().sum();
+    let cols_total: usize = level.cols.iter().flatten().sum();
+    if rows_total != cols_total {
+        errors.push(ValidationError::TotalsMismatch { rows_total, cols_total });
+    }
+
+    let lines = level.rows.iter().enumerate()
+        .map(|(i, clues)| (LineId::Row(i), clues, level.cols.len(), row_lines.get(i).copied()))
+        .chain(level.cols.iter().enumerate()
+            .map(|(i, clues)| (LineId::Col(i), clues, level.rows.len(), col_lines.get(i).copied())));
+
+    let (row_clues, col_clues) = (level.row_clues(), level.col_clues());
+    for (line, clues, available, source_line) in lines {
+        if clues.contains(&0) {
+            errors.push(ValidationError::ZeroLengthClue { line, source_line });
+        }
+        let colored = match line {
+            LineId::Row(i) => &row_clues[i],
+            LineId::Col(i) => &col_clues[i]
+        };
+        // blocks of different colours need no gap between them
+        let gaps = colored.windows(2).filter(|x| x[0].1 == x[1].1).count();
+        let required = clues.iter().sum::<usize>() + gaps;
+        if required > available {
+            errors.push(ValidationError::LineTooShort { line, required, available, source_line });
+        }
+    }
+
+    errors.extend(check_colors(level));
+
+    if let Some(solution) = &level.solution {
+        if level.is_colored() {
+            errors.push(ValidationError::ColoredSolution);
+        }
+        else {
+            errors.extend(check_solution(level, solution));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    }
+    else {
+        Err(LevelError::Invalid(errors))
+    }
+}
+
+fn check_colors(level: &LevelDescriptionTemplate) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let palette = level.colors();
+    if palette.len() > MAX_COLORS {
+        errors.push(ValidationError::TooManyColors { colors: palette.len() });
+    }
+    for color in palette.iter().filter(|x| x.to_rgb().is_none()) {
+        errors.push(ValidationError::PaletteColor { name: color.name.clone() });
+    }
+
+    let lines = [(&level.rows, &level.row_colors, LineId::Row as fn(usize) -> LineId), (&level.cols, &level.col_colors, LineId::Col)];
+    for (clues, colors, line_id) in lines {
+        // a level without colours uses the first one everywhere
+        if colors.is_empty() {
+            continue;
+        }
+        for (i, line) in clues.iter().enumerate() {
+            let line_colors = colors.get(i).map(|x| &x[..]).unwrap_or(&[]);
+            if line_colors.len() != line.len() {
+                errors.push(ValidationError::ColorCount { line: line_id(i), clues: line.len(), colors: line_colors.len() });
+            }
+            if let Some(color) = line_colors.iter().find(|x| **x as usize >= palette.len()) {
+                errors.push(ValidationError::UnknownColor { line: line_id(i), color: *color as usize });
+            }
+        }
+    }
+
+    if level.is_colored() {
+        let total = |lines: Vec<Vec<Clue>>, color: usize| -> usize {
+            lines.iter().flatten().filter(|x| x.1 as usize == color).map(|x| x.0).sum()
+        };
+        for (index, color) in palette.iter().enumerate() {
+            let (rows_total, cols_total) = (total(level.row_clues(), index), total(level.col_clues(), index));
+            if rows_total != cols_total {
+                errors.push(ValidationError::ColorTotalsMismatch { color: color.name.clone(), rows_total, cols_total });
+            }
+        }
+    }
+    errors
+}
+
+fn check_solution(level: &LevelDescriptionTemplate, solution: &Solution) -> Vec<ValidationError> {
+    let cells = match solution.to_cells() {
+        Ok(cells) => cells,
+        Err((row, col)) => return vec![ValidationError::SolutionSymbol { row, col }]
+    };
+
+    let (width, height) = (level.cols.len(), level.rows.len());
+    if cells.len() != height || cells.iter().any(|x| x.len() != width) {
+        return vec![ValidationError::SolutionSize {
+            solution_width: cells.iter().map(|x| x.len()).max().unwrap_or(0),
+            solution_height: cells.len(),
+            width,
+            height
+        }];
+    }
+
+    let expected = level_from_picture(&cells);
+    let rows = (0..height).filter(|i| expected.rows[*i] != level.rows[*i]).map(LineId::Row);
+    let cols = (0..width).filter(|i| expected.cols[*i] != level.cols[*i]).map(LineId::Col);
+    rows.chain(cols).map(|line| ValidationError::SolutionMismatch { line }).collect()
+}
+
+/// Best effort lookup of the 1-based source lines of the items of a top level yaml list,
+/// only block style lists (`- [1, 2]` per line) are recognized.
+pub fn yaml_item_lines(text: &str, key: &str) -> Vec<usize> {
+    let mut result = Vec::new();
+    let mut in_section = false;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(|c: char| c.is_whitespace() || c == '-') {
+            in_section = line.trim_end().strip_suffix(':') == Some(key);
+        }
+        else if in_section && trimmed.starts_with('-') {
+            result.push(i + 1);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reports_every_problem_with_source_lines() {
+        let text = "rows:\n  - [1, 1]\n  - [0]\n\ncols:\n  - [2]\n  - [1]\n";
+        let level: LevelDescriptionTemplate = serde_yaml::from_str(text).unwrap();
+        let result = validate(&level, &yaml_item_lines(text, "rows"), &yaml_item_lines(text, "cols"));
+        match result {
+            Err(LevelError::Invalid(errors)) => assert_eq!(errors, vec![
+                ValidationError::TotalsMismatch { rows_total: 2, cols_total: 3 },
+                ValidationError::LineTooShort { line: LineId::Row(0), required: 3, available: 2, source_line: Some(2) },
+                ValidationError::ZeroLengthClue { line: LineId::Row(1), source_line: Some(3) }
+            ]),
+            _ => panic!("level must be rejected")
+        }
+    }
+
+    #[test]
+    fn test_solution_is_checked_against_clues() {
+        let text = "rows:\n  - [1]\n  - [2]\ncols:\n  - [2]\n  - [1]\nsolution:\n  - '#.'\n  - '##'\n";
+        let level: LevelDescriptionTemplate = serde_yaml::from_str(text).unwrap();
+        assert!(level.validate().is_ok());
+
+        let text = "rows:\n  - [1]\n  - [2]\ncols:\n  - [2]\n  - [1]\nsolution:\n  - [0, 1]\n  - [1, 1]\n";
+        let level: LevelDescriptionTemplate = serde_yaml::from_str(text).unwrap();
+        match level.validate() {
+            Err(LevelError::Invalid(errors)) => assert_eq!(errors, vec![
+                ValidationError::SolutionMismatch { line: LineId::Col(0) },
+                ValidationError::SolutionMismatch { line: LineId::Col(1) }
+            ]),
+            _ => panic!("solution contradicts the columns")
+        }
+    }
+
+    #[test]
+    fn test_colors_are_checked() {
+        let text = "palette:\n  - {name: black, rgb: '000'}\n  - {name: red, rgb: ff0000}\n\nrows:\n  - [1, 1]\n  - [1]\nrow_colors:\n  - [1, 0]\n  - [1]\n\ncols:\n  - [2]\n  - [1]\ncol_colors:\n  - [1]\n  - [0]\n";
+        let level: LevelDescriptionTemplate = serde_yaml::from_str(text).unwrap();
+        // red and black touch in the first row
+        assert!(level.validate().is_ok());
+
+        let text = "palette:\n  - {name: black, rgb: '000'}\n  - {name: red, rgb: red}\n\nrows:\n  - [1, 1]\nrow_colors:\n  - [1, 2]\n\ncols:\n  - [1]\n  - [1]\ncol_colors:\n  - [1]\n";
+        let level: LevelDescriptionTemplate = serde_yaml::from_str(text).unwrap();
+        match level.validate() {
+            Err(LevelError::Invalid(errors)) => assert_eq!(errors, vec![
+                ValidationError::PaletteColor { name: "red".to_string() },
+                ValidationError::UnknownColor { line: LineId::Row(0), color: 2 },
+                ValidationError::ColorCount { line: LineId::Col(1), clues: 1, colors: 0 },
+                ValidationError::ColorTotalsMismatch { color: "black".to_string(), rows_total: 0, cols_total: 1 }
+            ]),
+            _ => panic!("level must be rejected")
+        }
+    }
+}